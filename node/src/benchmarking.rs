@@ -129,8 +129,8 @@ pub fn create_benchmark_extrinsic(
             period,
             best_block.saturated_into(),
         )),
-        frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
         pallet_feeless::CheckRate::<runtime::Runtime>::new(),
+        frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
         frame_system::CheckWeight::<runtime::Runtime>::new(),
         pallet_transaction_payment::ChargeTransactionPayment::<runtime::Runtime>::from(0),
         frame_metadata_hash_extension::CheckMetadataHash::<runtime::Runtime>::new(false),
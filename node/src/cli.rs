@@ -1,5 +1,30 @@
 use sc_cli::RunCmd;
 
+/// How blocks are sealed when Aura and GRANDPA are replaced by manual sealing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+    /// Seals a block as soon as a transaction enters the pool.
+    Instant,
+    /// Seals blocks only on `engine_createBlock` RPC calls.
+    Manual,
+    /// Seals a block every given number of milliseconds.
+    Interval(u64),
+}
+
+impl std::str::FromStr for Sealing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "instant" => Self::Instant,
+            "manual" => Self::Manual,
+            millis => Self::Interval(millis.parse().map_err(|_| {
+                format!("invalid sealing `{millis}`, expected `instant`, `manual` or milliseconds")
+            })?),
+        })
+    }
+}
+
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
     #[command(subcommand)]
@@ -7,6 +32,36 @@ pub struct Cli {
 
     #[clap(flatten)]
     pub run: RunCmd,
+
+    /// Seal blocks without Aura and GRANDPA, for development and tests: `instant`, `manual`
+    /// or a block time in milliseconds.
+    ///
+    /// The `engine_createBlock` and `engine_finalizeBlock` RPCs are available in all modes.
+    /// Each block advances the timestamp by exactly one slot.
+    #[arg(long)]
+    pub sealing: Option<Sealing>,
+
+    /// Maximum number of transactions a peer may gossip to the node per
+    /// `--tx-gossip-period`.
+    ///
+    /// Notifications over the limit are dropped before validation and lower the reputation
+    /// of the peer.
+    #[arg(long, default_value_t = 1_000)]
+    pub tx_gossip_limit: u32,
+
+    /// Length in milliseconds of the period of `--tx-gossip-limit`.
+    #[arg(long, default_value_t = 6_000)]
+    pub tx_gossip_period: u64,
+}
+
+impl Cli {
+    /// Limits of incoming transaction gossip.
+    pub fn gossip_limits(&self) -> crate::gossip::GossipLimits {
+        crate::gossip::GossipLimits {
+            max_transactions: self.tx_gossip_limit,
+            period: std::time::Duration::from_millis(self.tx_gossip_period),
+        }
+    }
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -64,7 +64,7 @@ pub fn run() -> sc_cli::Result<()> {
                     task_manager,
                     import_queue,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, cli.sealing)?;
                 Ok((cmd.run(client, import_queue), task_manager))
             })
         }
@@ -75,7 +75,7 @@ pub fn run() -> sc_cli::Result<()> {
                     client,
                     task_manager,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, cli.sealing)?;
                 Ok((cmd.run(client, config.database), task_manager))
             })
         }
@@ -86,7 +86,7 @@ pub fn run() -> sc_cli::Result<()> {
                     client,
                     task_manager,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, cli.sealing)?;
                 Ok((cmd.run(client, config.chain_spec), task_manager))
             })
         }
@@ -98,7 +98,7 @@ pub fn run() -> sc_cli::Result<()> {
                     task_manager,
                     import_queue,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, cli.sealing)?;
                 Ok((cmd.run(client, import_queue), task_manager))
             })
         }
@@ -114,7 +114,7 @@ pub fn run() -> sc_cli::Result<()> {
                     task_manager,
                     backend,
                     ..
-                } = service::new_partial(&config)?;
+                } = service::new_partial(&config, cli.sealing)?;
                 let aux_revert = Box::new(|client, _, blocks| {
                     sc_consensus_grandpa::revert(client, blocks)?;
                     Ok(())
@@ -143,7 +143,8 @@ pub fn run() -> sc_cli::Result<()> {
                         ))
                     }
                     BenchmarkCmd::Block(cmd) => {
-                        let PartialComponents { client, .. } = service::new_partial(&config)?;
+                        let PartialComponents { client, .. } =
+                            service::new_partial(&config, cli.sealing)?;
                         cmd.run(client)
                     }
                     #[cfg(not(feature = "runtime-benchmarks"))]
@@ -155,15 +156,15 @@ pub fn run() -> sc_cli::Result<()> {
                     BenchmarkCmd::Storage(cmd) => {
                         let PartialComponents {
                             client, backend, ..
-                        } = service::new_partial(&config)?;
+                        } = service::new_partial(&config, cli.sealing)?;
                         let db = backend.expose_db();
                         let storage = backend.expose_storage();
-                        let shared_cache = backend.expose_shared_trie_cache();
 
-                        cmd.run(config, client, db, storage, shared_cache)
+                        cmd.run(config, client, db, storage)
                     }
                     BenchmarkCmd::Overhead(cmd) => {
-                        let PartialComponents { client, .. } = service::new_partial(&config)?;
+                        let PartialComponents { client, .. } =
+                            service::new_partial(&config, cli.sealing)?;
                         let ext_builder = RemarkBuilder::new(client.clone());
 
                         cmd.run(
@@ -176,7 +177,8 @@ pub fn run() -> sc_cli::Result<()> {
                         )
                     }
                     BenchmarkCmd::Extrinsic(cmd) => {
-                        let PartialComponents { client, .. } = service::new_partial(&config)?;
+                        let PartialComponents { client, .. } =
+                            service::new_partial(&config, cli.sealing)?;
                         // Register the *Remark* and *TKA* builders.
                         let ext_factory = ExtrinsicFactory(vec![
                             Box::new(RemarkBuilder::new(client.clone())),
@@ -201,6 +203,7 @@ pub fn run() -> sc_cli::Result<()> {
         }
         None => {
             let runner = cli.create_runner(&cli.run)?;
+            let gossip_limits = cli.gossip_limits();
             runner.run_node_until_exit(|config| async move {
                 match config.network.network_backend.unwrap_or_default() {
 					sc_network::config::NetworkBackendType::Libp2p => service::new_full::<
@@ -208,10 +211,10 @@ pub fn run() -> sc_cli::Result<()> {
 							solochain_template_runtime::opaque::Block,
 							<solochain_template_runtime::opaque::Block as sp_runtime::traits::Block>::Hash,
 						>,
-					>(config)
+					>(config, cli.sealing, gossip_limits)
 					.map_err(sc_cli::Error::Service),
 					sc_network::config::NetworkBackendType::Litep2p =>
-						service::new_full::<sc_network::Litep2pNetworkBackend>(config)
+						service::new_full::<sc_network::Litep2pNetworkBackend>(config, cli.sealing, gossip_limits)
 							.map_err(sc_cli::Error::Service),
 				}
             })
@@ -0,0 +1,588 @@
+//! Node-side support for the feeless runtime.
+//!
+//! [`FeelessPool`] wraps the transaction pool to report, through Prometheus, the transactions
+//! rejected by the runtime and in particular by the `CheckRate` rate limiter, which are
+//! otherwise invisible in the default transaction pool metrics.
+//!
+//! It also remembers the accounts whose quota is used up until their period resets, so that
+//! their transactions, gossiped or submitted, are dropped without a runtime call.
+
+use codec::{Decode, Encode};
+use pallet_feeless::QUOTA_EXHAUSTED;
+use pallet_feeless_runtime_api::FeelessApi;
+use prometheus_endpoint::{
+    register, Counter, CounterVec, Gauge, Opts, PrometheusError, Registry, U64,
+};
+use sc_transaction_pool_api::{
+    error::{Error as PoolError, IntoPoolError},
+    ChainEvent, ImportNotificationStream, InPoolTransaction, LocalTransactionFor,
+    LocalTransactionPool, MaintainedTransactionPool, PoolStatus, ReadyTransactions, TransactionFor,
+    TransactionPool, TransactionSource, TransactionStatusStreamFor, TxHash, TxInvalidityReportMap,
+};
+use schnellru::{ByLength, LruMap};
+use solochain_template_runtime::{
+    check_rate_stamp, configs::FeelessExemptCalls, opaque::Block, AccountId, BlockNumber,
+    UncheckedExtrinsic,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+    generic::Preamble, traits::Block as BlockT, transaction_validity::InvalidTransaction,
+    MultiAddress,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const LOG_TARGET: &str = "feeless";
+
+/// Feeless Prometheus metrics.
+pub struct Metrics {
+    invalid_transactions: CounterVec<U64>,
+    prefiltered_transactions: Counter<U64>,
+    throttle_cache_accounts: Gauge<U64>,
+    throttled_accounts: Gauge<U64>,
+    pool_senders: Gauge<U64>,
+    pool_max_transactions_per_sender: Gauge<U64>,
+    block_active_accounts: Gauge<U64>,
+    block_transactions: Gauge<U64>,
+    block_bytes: Gauge<U64>,
+    block_accounts_at_quota: Gauge<U64>,
+}
+
+impl Metrics {
+    /// Registers the metrics at the given Prometheus registry.
+    pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+        Ok(Self {
+            invalid_transactions: register(
+                CounterVec::new(
+                    Opts::new(
+                        "feeless_txpool_invalid_transactions_total",
+                        "Number of transactions rejected by the runtime, by reason. \
+                         Rate-limited transactions are reported as `quota_exhausted`",
+                    ),
+                    &["reason"],
+                )?,
+                registry,
+            )?,
+            prefiltered_transactions: register(
+                Counter::new(
+                    "feeless_txpool_prefiltered_transactions_total",
+                    "Number of transactions from accounts known to be rate limited, dropped \
+                     without runtime validation",
+                )?,
+                registry,
+            )?,
+            throttle_cache_accounts: register(
+                Gauge::new(
+                    "feeless_throttle_cache_accounts",
+                    "Number of rate-limited accounts remembered by the pool pre-filter",
+                )?,
+                registry,
+            )?,
+            throttled_accounts: register(
+                Gauge::new(
+                    "feeless_throttled_accounts",
+                    "Number of distinct accounts rate limited between the last two best blocks",
+                )?,
+                registry,
+            )?,
+            pool_senders: register(
+                Gauge::new(
+                    "feeless_txpool_senders",
+                    "Number of distinct signers with transactions in the pool",
+                )?,
+                registry,
+            )?,
+            pool_max_transactions_per_sender: register(
+                Gauge::new(
+                    "feeless_txpool_max_transactions_per_sender",
+                    "Highest number of transactions in the pool from a single signer",
+                )?,
+                registry,
+            )?,
+            block_active_accounts: register(
+                Gauge::new(
+                    "feeless_block_active_accounts",
+                    "Number of distinct accounts that transacted in the last block",
+                )?,
+                registry,
+            )?,
+            block_transactions: register(
+                Gauge::new(
+                    "feeless_block_transactions",
+                    "Number of transactions counted against quotas in the last block",
+                )?,
+                registry,
+            )?,
+            block_bytes: register(
+                Gauge::new(
+                    "feeless_block_bytes",
+                    "Bytes counted against quotas in the last block",
+                )?,
+                registry,
+            )?,
+            block_accounts_at_quota: register(
+                Gauge::new(
+                    "feeless_block_accounts_at_quota",
+                    "Number of accounts that used up their quota in the last block",
+                )?,
+                registry,
+            )?,
+        })
+    }
+}
+
+/// Transaction pool wrapper reporting feeless metrics and pre-filtering rate-limited accounts.
+///
+/// Every call is forwarded to the inner pool. Invalid transactions are counted by reason when
+/// they are submitted, and the pool content and the runtime usage statistics are sampled on
+/// each new best block.
+///
+/// When `CheckRate` rejects a transaction because its signer used up its quota, the block at
+/// which the quota resets is read through [`FeelessApi::quota`] and the signer is remembered in
+/// a LRU cache until then. Later transactions of the signer are rejected as exhausting
+/// resources without being validated by the runtime, except the calls of `FeelessExemptCalls`
+/// that the runtime confirms are exempt for the signer.
+/// A status change of a cached account only applies once its period resets.
+pub struct FeelessPool<P, C> {
+    inner: Arc<P>,
+    client: Arc<C>,
+    metrics: Option<Metrics>,
+    throttled: Mutex<HashSet<AccountId>>,
+    throttle_cache: Mutex<LruMap<AccountId, BlockNumber, ByLength>>,
+}
+
+impl<P, C> FeelessPool<P, C>
+where
+    P: TransactionPool<Block = Block>,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+    C::Api: FeelessApi<Block, AccountId, BlockNumber>,
+{
+    /// Wraps `inner`, remembering up to `cache_size` rate-limited accounts and registering the
+    /// metrics if a Prometheus registry is given.
+    pub fn new(
+        inner: Arc<P>,
+        client: Arc<C>,
+        cache_size: u32,
+        registry: Option<&Registry>,
+    ) -> Self {
+        let metrics = registry.and_then(|registry| {
+            Metrics::register(registry)
+                .map_err(|err| {
+                    log::warn!(target: LOG_TARGET, "Failed to register prometheus metrics: {}", err);
+                })
+                .ok()
+        });
+        Self {
+            inner,
+            client,
+            metrics,
+            throttled: Mutex::new(HashSet::new()),
+            throttle_cache: Mutex::new(LruMap::new(ByLength::new(cache_size))),
+        }
+    }
+
+    /// Returns whether `xt` is signed by an account whose quota is known to be used up for the
+    /// next block, forgetting the account if its period has reset.
+    ///
+    /// Stamped transactions are left to the pool, as a valid stamp lets them through whatever the
+    /// quota left.
+    fn is_throttled(&self, xt: &TransactionFor<P>) -> bool {
+        let mut cache = self.throttle_cache.lock().expect("not poisoned; qed");
+        if cache.len() == 0 {
+            return false;
+        }
+        let Some(decoded) = decode(xt) else {
+            return false;
+        };
+        let Some(who) = unstamped_signer(&decoded) else {
+            return false;
+        };
+        let info = self.client.info();
+        match cache.get(who) {
+            Some(resets_at) if info.best_number.saturating_add(1) < *resets_at => {
+                drop(cache);
+                !FeelessExemptCalls::matches(&decoded.function, &|_| true)
+                    || !self.is_exempt(info.best_hash, xt)
+            }
+            Some(_) => {
+                cache.remove(who);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Returns whether `xt` is exempt from rate limiting at `at`, which depends on its signer.
+    fn is_exempt(&self, at: <Block as BlockT>::Hash, xt: &TransactionFor<P>) -> bool {
+        self.client
+            .runtime_api()
+            .is_exempt(at, xt.clone())
+            .unwrap_or_else(|err| {
+                log::debug!(target: LOG_TARGET, "Failed to check exemption at {}: {}", at, err);
+                false
+            })
+    }
+
+    /// Returns the error of a transaction dropped by [`Self::is_throttled`].
+    fn prefiltered(&self) -> P::Error {
+        if let Some(metrics) = &self.metrics {
+            metrics.prefiltered_transactions.inc();
+        }
+        PoolError::InvalidTransaction(InvalidTransaction::Custom(QUOTA_EXHAUSTED)).into()
+    }
+
+    /// Remembers `who` until its quota resets, if its quota is used up at `at`.
+    fn note_throttled(&self, at: <Block as BlockT>::Hash, who: AccountId) {
+        match self.client.runtime_api().quota(at, who.clone()) {
+            // The rejection may come from the quota of a derivative account, or from a
+            // transaction larger than the remaining size.
+            Ok(quota) if quota.remaining_tx == Some(0) || quota.remaining_size == Some(0) => {
+                self.throttle_cache
+                    .lock()
+                    .expect("not poisoned; qed")
+                    .insert(who, quota.resets_at);
+            }
+            Ok(_) => {}
+            Err(err) => {
+                log::debug!(target: LOG_TARGET, "Failed to query feeless quota at {}: {}", at, err);
+            }
+        }
+    }
+
+    /// Records the outcome of a submission at `at`, passing the result through unchanged.
+    fn note_submission<R>(
+        &self,
+        at: <Block as BlockT>::Hash,
+        xt: &TransactionFor<P>,
+        result: Result<R, P::Error>,
+    ) -> Result<R, P::Error> {
+        result.map_err(|err| match err.into_pool_error() {
+            Ok(err) => {
+                if let PoolError::InvalidTransaction(invalid) = &err {
+                    let throttled = *invalid == InvalidTransaction::Custom(QUOTA_EXHAUSTED);
+                    let who = throttled.then(|| signer(xt)).flatten();
+                    if let Some(metrics) = &self.metrics {
+                        metrics
+                            .invalid_transactions
+                            .with_label_values(&[reason(invalid)])
+                            .inc();
+                        if let Some(who) = &who {
+                            self.throttled
+                                .lock()
+                                .expect("not poisoned; qed")
+                                .insert(who.clone());
+                        }
+                    }
+                    if let Some(who) = who {
+                        self.note_throttled(at, who);
+                    }
+                }
+                err.into()
+            }
+            Err(err) => err,
+        })
+    }
+
+    /// Samples the pool content and the runtime usage statistics at a new best block.
+    fn sample(&self, at: <Block as BlockT>::Hash) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+
+        let throttled = std::mem::take(&mut *self.throttled.lock().expect("not poisoned; qed"));
+        metrics.throttled_accounts.set(throttled.len() as u64);
+        metrics
+            .throttle_cache_accounts
+            .set(self.throttle_cache.lock().expect("not poisoned; qed").len() as u64);
+
+        let mut per_sender = HashMap::<AccountId, u64>::new();
+        let ready = self.inner.ready().map(|tx| signer(tx.data()));
+        let futures = self.inner.futures().into_iter().map(|tx| signer(tx.data()));
+        for who in ready.chain(futures).flatten() {
+            *per_sender.entry(who).or_default() += 1;
+        }
+        metrics.pool_senders.set(per_sender.len() as u64);
+        metrics
+            .pool_max_transactions_per_sender
+            .set(per_sender.values().copied().max().unwrap_or_default());
+
+        match self.client.runtime_api().last_block_usage(at) {
+            Ok(usage) => {
+                metrics
+                    .block_active_accounts
+                    .set(usage.active_accounts.into());
+                metrics.block_transactions.set(usage.transactions.into());
+                metrics.block_bytes.set(usage.bytes.into());
+                metrics
+                    .block_accounts_at_quota
+                    .set(usage.accounts_at_quota.into());
+            }
+            Err(err) => {
+                log::debug!(target: LOG_TARGET, "Failed to query feeless usage at {}: {}", at, err);
+            }
+        }
+    }
+}
+
+/// Decodes an opaque extrinsic of the pool into a runtime extrinsic.
+fn decode(xt: &<Block as BlockT>::Extrinsic) -> Option<UncheckedExtrinsic> {
+    UncheckedExtrinsic::decode(&mut &xt.encode()[..]).ok()
+}
+
+/// Returns the account that signed `xt`, if any.
+fn signer(xt: &<Block as BlockT>::Extrinsic) -> Option<AccountId> {
+    match decode(xt)?.preamble {
+        Preamble::Signed(MultiAddress::Id(who), ..) => Some(who),
+        _ => None,
+    }
+}
+
+/// Returns the account that signed `xt`, if `xt` carries no proof-of-work stamp.
+fn unstamped_signer(xt: &UncheckedExtrinsic) -> Option<&AccountId> {
+    match &xt.preamble {
+        Preamble::Signed(MultiAddress::Id(who), _, tx_ext)
+            if check_rate_stamp(tx_ext).is_none() =>
+        {
+            Some(who)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the metric label of an invalid transaction reason.
+fn reason(invalid: &InvalidTransaction) -> &'static str {
+    match invalid {
+        InvalidTransaction::Call => "call",
+        InvalidTransaction::Payment => "payment",
+        InvalidTransaction::Future => "future",
+        InvalidTransaction::Stale => "stale",
+        InvalidTransaction::BadProof => "bad_proof",
+        InvalidTransaction::AncientBirthBlock => "ancient_birth_block",
+        InvalidTransaction::ExhaustsResources => "exhausts_resources",
+        InvalidTransaction::Custom(QUOTA_EXHAUSTED) => "quota_exhausted",
+        InvalidTransaction::Custom(_) => "custom",
+        InvalidTransaction::BadMandatory => "bad_mandatory",
+        InvalidTransaction::MandatoryValidation => "mandatory_validation",
+        InvalidTransaction::BadSigner => "bad_signer",
+        InvalidTransaction::IndeterminateImplicit => "indeterminate_implicit",
+        InvalidTransaction::UnknownOrigin => "unknown_origin",
+    }
+}
+
+#[async_trait::async_trait]
+impl<P, C> TransactionPool for FeelessPool<P, C>
+where
+    P: TransactionPool<Block = Block>,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+    C::Api: FeelessApi<Block, AccountId, BlockNumber>,
+{
+    type Block = P::Block;
+    type Error = P::Error;
+    type Hash = P::Hash;
+    type InPoolTransaction = P::InPoolTransaction;
+
+    async fn submit_at(
+        &self,
+        at: <Self::Block as BlockT>::Hash,
+        source: TransactionSource,
+        xts: Vec<TransactionFor<Self>>,
+    ) -> Result<Vec<Result<TxHash<Self>, Self::Error>>, Self::Error> {
+        let throttled = xts
+            .iter()
+            .map(|xt| self.is_throttled(xt))
+            .collect::<Vec<_>>();
+        let forwarded = xts
+            .iter()
+            .zip(&throttled)
+            .filter(|(_, throttled)| !**throttled)
+            .map(|(xt, _)| xt.clone())
+            .collect();
+        let mut results = self
+            .inner
+            .submit_at(at, source, forwarded)
+            .await?
+            .into_iter();
+        Ok(xts
+            .iter()
+            .zip(throttled)
+            .map(|(xt, throttled)| {
+                if throttled {
+                    return Err(self.prefiltered());
+                }
+                let result = results
+                    .next()
+                    .expect("one result per forwarded transaction; qed");
+                self.note_submission(at, xt, result)
+            })
+            .collect())
+    }
+
+    async fn submit_one(
+        &self,
+        at: <Self::Block as BlockT>::Hash,
+        source: TransactionSource,
+        xt: TransactionFor<Self>,
+    ) -> Result<TxHash<Self>, Self::Error> {
+        if self.is_throttled(&xt) {
+            return Err(self.prefiltered());
+        }
+        let result = self.inner.submit_one(at, source, xt.clone()).await;
+        self.note_submission(at, &xt, result)
+    }
+
+    async fn submit_and_watch(
+        &self,
+        at: <Self::Block as BlockT>::Hash,
+        source: TransactionSource,
+        xt: TransactionFor<Self>,
+    ) -> Result<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
+        if self.is_throttled(&xt) {
+            return Err(self.prefiltered());
+        }
+        let result = self.inner.submit_and_watch(at, source, xt.clone()).await;
+        self.note_submission(at, &xt, result)
+    }
+
+    async fn ready_at(
+        &self,
+        at: <Self::Block as BlockT>::Hash,
+    ) -> Box<dyn ReadyTransactions<Item = Arc<Self::InPoolTransaction>> + Send> {
+        self.inner.ready_at(at).await
+    }
+
+    fn ready(&self) -> Box<dyn ReadyTransactions<Item = Arc<Self::InPoolTransaction>> + Send> {
+        self.inner.ready()
+    }
+
+    fn report_invalid(
+        &self,
+        at: Option<<Self::Block as BlockT>::Hash>,
+        invalid_tx_errors: TxInvalidityReportMap<TxHash<Self>>,
+    ) -> Vec<Arc<Self::InPoolTransaction>> {
+        self.inner.report_invalid(at, invalid_tx_errors)
+    }
+
+    fn futures(&self) -> Vec<Self::InPoolTransaction> {
+        self.inner.futures()
+    }
+
+    fn status(&self) -> PoolStatus {
+        self.inner.status()
+    }
+
+    fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
+        self.inner.import_notification_stream()
+    }
+
+    fn on_broadcasted(&self, propagations: HashMap<TxHash<Self>, Vec<String>>) {
+        self.inner.on_broadcasted(propagations)
+    }
+
+    fn hash_of(&self, xt: &TransactionFor<Self>) -> TxHash<Self> {
+        self.inner.hash_of(xt)
+    }
+
+    fn ready_transaction(&self, hash: &TxHash<Self>) -> Option<Arc<Self::InPoolTransaction>> {
+        self.inner.ready_transaction(hash)
+    }
+
+    async fn ready_at_with_timeout(
+        &self,
+        at: <Self::Block as BlockT>::Hash,
+        timeout: Duration,
+    ) -> Box<dyn ReadyTransactions<Item = Arc<Self::InPoolTransaction>> + Send> {
+        self.inner.ready_at_with_timeout(at, timeout).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<P, C> MaintainedTransactionPool for FeelessPool<P, C>
+where
+    P: MaintainedTransactionPool<Block = Block>,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+    C::Api: FeelessApi<Block, AccountId, BlockNumber>,
+{
+    async fn maintain(&self, event: ChainEvent<Self::Block>) {
+        let best = match &event {
+            ChainEvent::NewBestBlock { hash, .. } => Some(*hash),
+            ChainEvent::Finalized { .. } => None,
+        };
+        self.inner.maintain(event).await;
+        if let Some(hash) = best {
+            self.sample(hash);
+        }
+    }
+}
+
+impl<P, C> LocalTransactionPool for FeelessPool<P, C>
+where
+    P: TransactionPool<Block = Block> + LocalTransactionPool<Block = Block>,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+    C::Api: FeelessApi<Block, AccountId, BlockNumber>,
+{
+    type Block = Block;
+    type Error = <P as LocalTransactionPool>::Error;
+    type Hash = <P as LocalTransactionPool>::Hash;
+
+    fn submit_local(
+        &self,
+        at: <Self::Block as BlockT>::Hash,
+        xt: LocalTransactionFor<Self>,
+    ) -> Result<Self::Hash, Self::Error> {
+        self.inner.submit_local(at, xt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solochain_template_runtime::{Runtime, RuntimeCall, Signature, TxExtension};
+    use sp_keyring::Sr25519Keyring;
+
+    /// Returns a remark of Alice, signed with a dummy signature, carrying `check_rate`.
+    fn remark(check_rate: pallet_feeless::CheckRate<Runtime>) -> UncheckedExtrinsic {
+        let tx_ext: TxExtension = (
+            frame_system::CheckNonZeroSender::<Runtime>::new(),
+            frame_system::CheckSpecVersion::<Runtime>::new(),
+            frame_system::CheckTxVersion::<Runtime>::new(),
+            frame_system::CheckGenesis::<Runtime>::new(),
+            frame_system::CheckEra::<Runtime>::from(sp_runtime::generic::Era::Immortal),
+            check_rate,
+            frame_system::CheckNonce::<Runtime>::from(0),
+            frame_system::CheckWeight::<Runtime>::new(),
+            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+            frame_metadata_hash_extension::CheckMetadataHash::<Runtime>::new(false),
+        );
+        UncheckedExtrinsic::new_signed(
+            RuntimeCall::System(frame_system::Call::remark { remark: vec![] }),
+            Sr25519Keyring::Alice.to_account_id().into(),
+            Signature::Sr25519(sp_core::sr25519::Signature::default()),
+            tx_ext,
+        )
+    }
+
+    #[test]
+    fn stamped_transactions_are_not_prefiltered() {
+        let alice = Sr25519Keyring::Alice.to_account_id();
+        assert_eq!(
+            unstamped_signer(&remark(pallet_feeless::CheckRate::new())),
+            Some(&alice)
+        );
+        assert_eq!(
+            unstamped_signer(&remark(pallet_feeless::CheckRate::with_stamp(7))),
+            None
+        );
+
+        // The stamp survives the round trip through the opaque extrinsic of the pool.
+        let opaque = <Block as BlockT>::Extrinsic::decode(
+            &mut &remark(pallet_feeless::CheckRate::with_stamp(7)).encode()[..],
+        )
+        .unwrap();
+        assert_eq!(unstamped_signer(&decode(&opaque).unwrap()), None);
+    }
+}
@@ -0,0 +1,803 @@
+//! Per-peer limits on incoming transaction gossip.
+//!
+//! The runtime rate limits transactions per account, but a single peer can still flood the
+//! transactions handler with transactions signed by many accounts, each of them validated by
+//! the runtime before being rejected. [`build_network_advanced`] builds the network like
+//! [`sc_service::build_network_advanced`], except that the transactions handler receives its
+//! notifications through [`RateLimitedTransactions`]. Notifications of a peer that gossips more
+//! transactions than its [`GossipLimits`] allow are dropped before reaching the handler, and the
+//! reputation of the peer is lowered in the peer store, so that a peer going over its limit in
+//! three periods less than a minute and a half apart is banned and disconnected.
+//! The usage of a peer outlives its connection, so reconnecting does not reset it.
+
+use crate::service::FullClient;
+use codec::{Compact, Decode};
+use futures::{FutureExt, StreamExt};
+use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use sc_client_api::BlockchainEvents;
+use sc_consensus::ImportQueue;
+use sc_network::{
+    config::{NotificationHandshake, Params, ProtocolId, SetConfig},
+    error::Error as NetworkError,
+    peer_store::{PeerStoreProvider, BANNED_THRESHOLD},
+    request_responses::IncomingRequest,
+    service::traits::{MessageSink, NotificationEvent, PeerStore},
+    NetworkBackend, NetworkBlock, NotificationMetrics, NotificationService, PeerId, ProtocolName,
+    ReputationChange,
+};
+use sc_network_light::light_client_requests::handler::LightClientRequestHandler;
+use sc_network_sync::SyncingService;
+use sc_network_transactions::{TransactionsHandlerController, TransactionsHandlerPrototype};
+use sc_service::{BuildNetworkAdvancedParams, Error as ServiceError, TransactionPoolAdapter};
+use sc_transaction_pool_api::TransactionPool;
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
+use schnellru::{ByLength, LruMap};
+use solochain_template_runtime::opaque::Block;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{
+    cell::RefCell,
+    convert::Infallible,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+const LOG_TARGET: &str = "feeless";
+
+type Hash = <Block as BlockT>::Hash;
+
+/// Reputation change of a peer going over its gossip limit in a period.
+///
+/// It is half of the ban threshold. As reputations decay by half in about 140 seconds, a peer
+/// going over its limit in three periods less than a minute and a half apart is banned.
+const GOSSIP_LIMIT_EXCEEDED: ReputationChange =
+    ReputationChange::new(BANNED_THRESHOLD / 2, "Transaction gossip limit exceeded");
+
+/// Reputation change of a peer for each other notification dropped in a period where it is over
+/// its gossip limit.
+///
+/// It matches the penalty of a bad transaction in the transactions handler.
+const GOSSIP_OVER_LIMIT: ReputationChange =
+    ReputationChange::new(-(1 << 12), "Transaction gossiped over limit");
+
+/// Transaction gossip Prometheus metrics.
+pub struct Metrics {
+    dropped_transactions: Counter<U64>,
+    rate_limited_peers: Counter<U64>,
+}
+
+impl Metrics {
+    /// Registers the metrics at the given Prometheus registry.
+    pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+        Ok(Self {
+            dropped_transactions: register(
+                Counter::new(
+                    "feeless_gossip_dropped_transactions_total",
+                    "Number of transactions gossiped by peers over their limit, dropped before \
+                     validation",
+                )?,
+                registry,
+            )?,
+            rate_limited_peers: register(
+                Counter::new(
+                    "feeless_gossip_rate_limited_peers_total",
+                    "Number of times a peer exceeded its transaction gossip limit in a period",
+                )?,
+                registry,
+            )?,
+        })
+    }
+}
+
+/// Number of transactions each peer may gossip to the node in a period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GossipLimits {
+    /// Maximum number of transactions per peer and period.
+    pub max_transactions: u32,
+    /// Length of a period, starting at the first notification of the peer.
+    pub period: Duration,
+}
+
+/// Maximum number of peers whose gossip usage is remembered.
+///
+/// It is well above the default number of peers, so that a peer cannot reset its usage by
+/// reconnecting, except by cycling through as many identities.
+const MAX_TRACKED_PEERS: u32 = 1024;
+
+/// Transactions gossiped by a peer in its current period.
+#[derive(Debug)]
+struct PeerUsage {
+    period_start: Instant,
+    transactions: u32,
+}
+
+/// Outcome of [`GossipLimiter::admit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Admission {
+    /// The notification is within the limit of the peer.
+    Admitted,
+    /// The notification brings the peer over its limit in the current period.
+    Limited,
+    /// The peer was already over its limit in the current period.
+    Dropped,
+}
+
+/// Counts the transactions gossiped by each peer in its current period.
+///
+/// The usage of a peer is kept when it disconnects, so that it is not reset by reconnecting.
+/// Only the `max_peers` most recently active peers are remembered.
+struct GossipLimiter {
+    limits: GossipLimits,
+    peers: LruMap<PeerId, PeerUsage, ByLength>,
+}
+
+impl std::fmt::Debug for GossipLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GossipLimiter")
+            .field("limits", &self.limits)
+            .field("peers", &self.peers.len())
+            .finish()
+    }
+}
+
+impl GossipLimiter {
+    fn new(limits: GossipLimits, max_peers: u32) -> Self {
+        Self {
+            limits,
+            peers: LruMap::new(ByLength::new(max_peers)),
+        }
+    }
+
+    /// Counts `count` transactions gossiped by `peer` at `now`.
+    ///
+    /// A period starts at the first notification of the peer after the previous one ended.
+    /// Dropped transactions still count towards the limit, so a flooding peer stays limited
+    /// until it slows down.
+    fn admit(&mut self, peer: PeerId, count: u32, now: Instant) -> Admission {
+        let period = self.limits.period;
+        let Some(usage) = self.peers.get_or_insert(peer, || PeerUsage {
+            period_start: now,
+            transactions: 0,
+        }) else {
+            return Admission::Admitted;
+        };
+        if now.saturating_duration_since(usage.period_start) >= period {
+            *usage = PeerUsage {
+                period_start: now,
+                transactions: 0,
+            };
+        }
+
+        let was_within_limit = usage.transactions <= self.limits.max_transactions;
+        usage.transactions = usage.transactions.saturating_add(count);
+        if usage.transactions <= self.limits.max_transactions {
+            Admission::Admitted
+        } else if was_within_limit {
+            Admission::Limited
+        } else {
+            Admission::Dropped
+        }
+    }
+}
+
+/// Notification service of the transactions protocol enforcing [`GossipLimits`].
+///
+/// Every call is forwarded to the inner service. Received notifications are counted per peer
+/// by the number of transactions they carry, and those exceeding the limit of the peer in its
+/// current period are dropped as a whole and reported to the peer store.
+pub struct RateLimitedTransactions {
+    inner: Box<dyn NotificationService>,
+    limiter: GossipLimiter,
+    peer_store: Arc<dyn PeerStoreProvider>,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl std::fmt::Debug for RateLimitedTransactions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimitedTransactions")
+            .field("inner", &self.inner)
+            .field("limiter", &self.limiter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RateLimitedTransactions {
+    /// Wraps the notification service of the transactions protocol.
+    pub fn new(
+        inner: Box<dyn NotificationService>,
+        limits: GossipLimits,
+        peer_store: Arc<dyn PeerStoreProvider>,
+        metrics: Option<Arc<Metrics>>,
+    ) -> Self {
+        Self {
+            inner,
+            limiter: GossipLimiter::new(limits, MAX_TRACKED_PEERS),
+            peer_store,
+            metrics,
+        }
+    }
+
+    /// Counts the transactions of a notification of `peer` received at `now` and returns
+    /// whether it is within the limits.
+    fn admit(&mut self, peer: PeerId, notification: &[u8], now: Instant) -> bool {
+        // Notifications are SCALE-encoded vectors of transactions, prefixed by their length.
+        // Undecodable ones are left to the transactions handler, which penalizes their sender.
+        let Ok(Compact(count)) = Compact::<u32>::decode(&mut &notification[..]) else {
+            return true;
+        };
+
+        let admission = self.limiter.admit(peer, count, now);
+        if admission == Admission::Admitted {
+            return true;
+        }
+
+        let limits = self.limiter.limits;
+        log::debug!(
+            target: LOG_TARGET,
+            "Dropping {count} transactions gossiped by {peer} over its limit of {} per {:?}",
+            limits.max_transactions,
+            limits.period,
+        );
+        self.peer_store.report_peer(
+            peer,
+            if admission == Admission::Limited {
+                GOSSIP_LIMIT_EXCEEDED
+            } else {
+                GOSSIP_OVER_LIMIT
+            },
+        );
+        if let Some(metrics) = &self.metrics {
+            metrics.dropped_transactions.inc_by(count.into());
+            if admission == Admission::Limited {
+                metrics.rate_limited_peers.inc();
+            }
+        }
+        false
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationService for RateLimitedTransactions {
+    async fn open_substream(&mut self, peer: PeerId) -> Result<(), ()> {
+        self.inner.open_substream(peer).await
+    }
+
+    async fn close_substream(&mut self, peer: PeerId) -> Result<(), ()> {
+        self.inner.close_substream(peer).await
+    }
+
+    fn send_sync_notification(&mut self, peer: &PeerId, notification: Vec<u8>) {
+        self.inner.send_sync_notification(peer, notification)
+    }
+
+    async fn send_async_notification(
+        &mut self,
+        peer: &PeerId,
+        notification: Vec<u8>,
+    ) -> Result<(), NetworkError> {
+        self.inner.send_async_notification(peer, notification).await
+    }
+
+    async fn set_handshake(&mut self, handshake: Vec<u8>) -> Result<(), ()> {
+        self.inner.set_handshake(handshake).await
+    }
+
+    fn try_set_handshake(&mut self, handshake: Vec<u8>) -> Result<(), ()> {
+        self.inner.try_set_handshake(handshake)
+    }
+
+    async fn next_event(&mut self) -> Option<NotificationEvent> {
+        loop {
+            let event = self.inner.next_event().await?;
+            if let NotificationEvent::NotificationReceived { peer, notification } = &event {
+                if !self.admit(*peer, notification, Instant::now()) {
+                    continue;
+                }
+            }
+            return Some(event);
+        }
+    }
+
+    fn clone(&mut self) -> Result<Box<dyn NotificationService>, ()> {
+        Ok(Box::new(Self::new(
+            self.inner.clone()?,
+            self.limiter.limits,
+            self.peer_store.clone(),
+            self.metrics.clone(),
+        )))
+    }
+
+    fn protocol(&self) -> &ProtocolName {
+        self.inner.protocol()
+    }
+
+    fn message_sink(&self, peer: &PeerId) -> Option<Box<dyn MessageSink>> {
+        self.inner.message_sink(peer)
+    }
+}
+
+thread_local! {
+    /// Limits of the transactions protocol being created by [`transactions_protocol`].
+    static GOSSIP_LIMITS: RefCell<Option<(GossipLimits, Option<Arc<Metrics>>)>> =
+        const { RefCell::new(None) };
+}
+
+/// Network backend type creating the protocols of `N` and wrapping the notification service
+/// in [`RateLimitedTransactions`].
+///
+/// [`TransactionsHandlerPrototype::new`] only creates its protocol through the static
+/// [`NetworkBackend::notification_config`], which does not take the limits, so
+/// [`transactions_protocol`] passes them through a thread local set around the call. The type
+/// cannot be instantiated: it only stands for `N` when creating the transactions protocol.
+struct TransactionsBackend<N>(Infallible, PhantomData<fn() -> N>);
+
+#[async_trait::async_trait]
+impl<N> NetworkBackend<Block, Hash> for TransactionsBackend<N>
+where
+    N: NetworkBackend<Block, Hash>,
+{
+    type BitswapConfig = N::BitswapConfig;
+    type NetworkService<B, H> = N::NetworkService<B, H>;
+    type NotificationProtocolConfig = N::NotificationProtocolConfig;
+    type PeerStore = N::PeerStore;
+    type RequestResponseProtocolConfig = N::RequestResponseProtocolConfig;
+
+    fn new(_: Params<Block, Hash, Self>) -> Result<Self, NetworkError> {
+        Err(NetworkError::Io(std::io::Error::other(
+            "`TransactionsBackend` only creates the transactions protocol",
+        )))
+    }
+
+    fn network_service(&self) -> Arc<dyn sc_network::service::traits::NetworkService> {
+        match self.0 {}
+    }
+
+    fn peer_store(bootnodes: Vec<PeerId>, metrics_registry: Option<Registry>) -> Self::PeerStore {
+        N::peer_store(bootnodes, metrics_registry)
+    }
+
+    fn register_notification_metrics(registry: Option<&Registry>) -> NotificationMetrics {
+        N::register_notification_metrics(registry)
+    }
+
+    fn bitswap_server(
+        client: Arc<dyn sc_client_api::BlockBackend<Block> + Send + Sync>,
+    ) -> (
+        Pin<Box<dyn Future<Output = ()> + Send>>,
+        Self::BitswapConfig,
+    ) {
+        N::bitswap_server(client)
+    }
+
+    fn notification_config(
+        protocol_name: ProtocolName,
+        fallback_names: Vec<ProtocolName>,
+        max_notification_size: u64,
+        handshake: Option<NotificationHandshake>,
+        set_config: SetConfig,
+        metrics: NotificationMetrics,
+        peerstore_handle: Arc<dyn PeerStoreProvider>,
+    ) -> (
+        Self::NotificationProtocolConfig,
+        Box<dyn NotificationService>,
+    ) {
+        let (config, service) = N::notification_config(
+            protocol_name,
+            fallback_names,
+            max_notification_size,
+            handshake,
+            set_config,
+            metrics,
+            peerstore_handle.clone(),
+        );
+        let (limits, metrics) = GOSSIP_LIMITS.with_borrow_mut(Option::take).expect(
+            "`TransactionsBackend` only creates the protocol of `transactions_protocol`, which \
+             sets the limits on this thread; qed",
+        );
+        let service = RateLimitedTransactions::new(service, limits, peerstore_handle, metrics);
+
+        (config, Box::new(service))
+    }
+
+    fn request_response_config(
+        protocol_name: ProtocolName,
+        fallback_names: Vec<ProtocolName>,
+        max_request_size: u64,
+        max_response_size: u64,
+        request_timeout: Duration,
+        inbound_queue: Option<async_channel::Sender<IncomingRequest>>,
+    ) -> Self::RequestResponseProtocolConfig {
+        N::request_response_config(
+            protocol_name,
+            fallback_names,
+            max_request_size,
+            max_response_size,
+            request_timeout,
+            inbound_queue,
+        )
+    }
+
+    async fn run(mut self) {
+        match self.0 {}
+    }
+}
+
+/// Creates the transactions protocol of `N`, whose notifications are limited per peer by
+/// `limits`.
+///
+/// This relies on [`TransactionsHandlerPrototype::new`] creating its protocol on the calling
+/// thread, once. [`TransactionsBackend`] panics otherwise, and an error is returned if the
+/// protocol was not created through it, so that the limits are never silently skipped.
+fn transactions_protocol<N: NetworkBackend<Block, Hash>>(
+    protocol_id: ProtocolId,
+    genesis_hash: Hash,
+    fork_id: Option<&str>,
+    metrics: NotificationMetrics,
+    peer_store_handle: Arc<dyn PeerStoreProvider>,
+    limits: GossipLimits,
+    gossip_metrics: Option<Arc<Metrics>>,
+) -> Result<(TransactionsHandlerPrototype, N::NotificationProtocolConfig), ServiceError> {
+    GOSSIP_LIMITS.set(Some((limits, gossip_metrics)));
+    let protocol = TransactionsHandlerPrototype::new::<_, Block, TransactionsBackend<N>>(
+        protocol_id,
+        genesis_hash,
+        fork_id,
+        metrics,
+        peer_store_handle,
+    );
+    if GOSSIP_LIMITS.with_borrow_mut(Option::take).is_some() {
+        return Err(ServiceError::Other(
+            "The transactions protocol was created without its gossip limits".into(),
+        ));
+    }
+    Ok(protocol)
+}
+
+/// Builds the network service, the network status sinks and an RPC sender.
+///
+/// This is [`sc_service::build_network_advanced`], which the syncing engine is built for in the
+/// same way, except that the transactions protocol is created by [`transactions_protocol`]:
+/// upstream offers no way to wrap its notification service. It follows `sc-service` 0.50, and
+/// must be compared with upstream again when `sc-service` is upgraded.
+#[allow(clippy::type_complexity)]
+pub fn build_network_advanced<N, P, IQ>(
+    params: BuildNetworkAdvancedParams<Block, N, P, IQ, FullClient>,
+    gossip_limits: GossipLimits,
+) -> Result<
+    (
+        Arc<dyn sc_network::service::traits::NetworkService>,
+        TracingUnboundedSender<sc_rpc::system::Request<Block>>,
+        TransactionsHandlerController<Hash>,
+        Arc<SyncingService<Block>>,
+    ),
+    ServiceError,
+>
+where
+    N: NetworkBackend<Block, Hash>,
+    P: TransactionPool<Block = Block, Hash = Hash> + 'static,
+    IQ: ImportQueue<Block> + 'static,
+{
+    let BuildNetworkAdvancedParams {
+        role,
+        protocol_id,
+        fork_id,
+        ipfs_server,
+        announce_block,
+        mut net_config,
+        client,
+        transaction_pool,
+        spawn_handle,
+        import_queue,
+        sync_service,
+        block_announce_config,
+        network_service_provider,
+        metrics_registry,
+        metrics,
+    } = params;
+
+    let genesis_hash = client.info().genesis_hash;
+
+    let (handler, light_client_request_protocol_config) =
+        LightClientRequestHandler::new::<N>(&protocol_id, fork_id, client.clone());
+    spawn_handle.spawn(
+        "light-client-request-handler",
+        Some("networking"),
+        handler.run(),
+    );
+    net_config.add_request_response_protocol(light_client_request_protocol_config);
+
+    let bitswap_config = ipfs_server.then(|| {
+        let (handler, config) = N::bitswap_server(client.clone());
+        spawn_handle.spawn("bitswap-request-handler", Some("networking"), handler);
+        config
+    });
+
+    let gossip_metrics = match metrics_registry.map(Metrics::register).transpose() {
+        Ok(metrics) => metrics.map(Arc::new),
+        Err(err) => {
+            log::warn!(target: LOG_TARGET, "Failed to register gossip metrics: {err}");
+            None
+        }
+    };
+    let (transactions_handler_proto, transactions_config) = transactions_protocol::<N>(
+        protocol_id.clone(),
+        genesis_hash,
+        fork_id,
+        metrics.clone(),
+        net_config.peer_store_handle(),
+        gossip_limits,
+        gossip_metrics,
+    )?;
+    net_config.add_notification_protocol(transactions_config);
+
+    let peer_store = net_config.take_peer_store();
+    spawn_handle.spawn("peer-store", Some("networking"), peer_store.run());
+
+    let sync_service = Arc::new(sync_service);
+
+    let has_bootnodes = !net_config.network_config.boot_nodes.is_empty();
+    let network_mut = N::new(Params {
+        role,
+        executor: {
+            let spawn_handle = spawn_handle.clone();
+            Box::new(move |fut| {
+                spawn_handle.spawn("libp2p-node", Some("networking"), fut);
+            })
+        },
+        network_config: net_config,
+        genesis_hash,
+        protocol_id,
+        fork_id: fork_id.map(ToOwned::to_owned),
+        metrics_registry: metrics_registry.cloned(),
+        block_announce_config,
+        bitswap_config,
+        notification_metrics: metrics,
+    })?;
+    let network = network_mut.network_service().clone();
+
+    let (tx_handler, tx_handler_controller) = transactions_handler_proto.build(
+        network.clone(),
+        sync_service.clone(),
+        Arc::new(TransactionPoolAdapter::new(
+            transaction_pool,
+            client.clone(),
+        )),
+        metrics_registry,
+    )?;
+    spawn_handle.spawn_blocking(
+        "network-transactions-handler",
+        Some("networking"),
+        tx_handler.run(),
+    );
+
+    spawn_handle.spawn_blocking(
+        "chain-sync-network-service-provider",
+        Some("networking"),
+        network_service_provider.run(Arc::new(network.clone())),
+    );
+    spawn_handle.spawn("import-queue", None, {
+        let sync_service = sync_service.clone();
+        async move { import_queue.run(sync_service.as_ref()).await }
+    });
+
+    let (system_rpc_tx, system_rpc_rx) = tracing_unbounded("mpsc_system_rpc", 10_000);
+    spawn_handle.spawn(
+        "system-rpc-handler",
+        Some("networking"),
+        sc_service::build_system_rpc_future::<_, _, Hash>(
+            role,
+            network_mut.network_service(),
+            sync_service.clone(),
+            client.clone(),
+            system_rpc_rx,
+            has_bootnodes,
+        ),
+    );
+
+    // Like the network worker of `sc_service`, this future can take long to process its
+    // queue and runs on a blocking thread.
+    spawn_handle.spawn_blocking(
+        "network-worker",
+        Some("networking"),
+        network_future(network_mut, client, sync_service.clone(), announce_block),
+    );
+
+    Ok((network, system_rpc_tx, tx_handler_controller, sync_service))
+}
+
+/// Drives the network and notifies the syncing service of imported and finalized blocks.
+async fn network_future<N: NetworkBackend<Block, Hash>>(
+    network: N,
+    client: Arc<FullClient>,
+    sync_service: Arc<SyncingService<Block>>,
+    announce_imported_blocks: bool,
+) {
+    let mut imported_blocks = client.import_notification_stream().fuse();
+    let mut finalized_blocks = client.finality_notification_stream().fuse();
+    let mut network_run = Box::pin(network.run().fuse());
+
+    loop {
+        futures::select! {
+            notification = imported_blocks.next() => {
+                let Some(notification) = notification else {
+                    log::debug!(target: LOG_TARGET, "Block import stream has terminated");
+                    return;
+                };
+                if announce_imported_blocks {
+                    sync_service.announce_block(notification.hash, None);
+                }
+                if notification.is_new_best {
+                    sync_service.new_best_block_imported(
+                        notification.hash,
+                        *notification.header.number(),
+                    );
+                }
+            }
+            notification = finalized_blocks.select_next_some() => {
+                sync_service.on_block_finalized(notification.hash, notification.header);
+            }
+            _ = network_run => {
+                log::debug!(target: LOG_TARGET, "Network worker has terminated");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+    use sc_network::{peer_store::PeerStore as PeerStoreImpl, NetworkWorker};
+
+    const LIMITS: GossipLimits = GossipLimits {
+        max_transactions: 10,
+        period: Duration::from_secs(6),
+    };
+
+    #[test]
+    fn admit_limits_transactions_per_period() {
+        let mut limiter = GossipLimiter::new(LIMITS, 8);
+        let (peer, other) = (PeerId::random(), PeerId::random());
+        let now = Instant::now();
+
+        assert_eq!(limiter.admit(peer, 6, now), Admission::Admitted);
+        assert_eq!(limiter.admit(peer, 4, now), Admission::Admitted);
+        assert_eq!(limiter.admit(peer, 1, now), Admission::Limited);
+        assert_eq!(limiter.admit(peer, 1, now), Admission::Dropped);
+
+        // Peers are limited separately.
+        assert_eq!(limiter.admit(other, 10, now), Admission::Admitted);
+    }
+
+    #[test]
+    fn admit_resets_the_usage_after_a_period() {
+        let mut limiter = GossipLimiter::new(LIMITS, 8);
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(limiter.admit(peer, 11, now), Admission::Limited);
+        assert_eq!(
+            limiter.admit(peer, 1, now + LIMITS.period - Duration::from_millis(1)),
+            Admission::Dropped
+        );
+        assert_eq!(
+            limiter.admit(peer, 10, now + LIMITS.period),
+            Admission::Admitted
+        );
+        assert_eq!(
+            limiter.admit(peer, 1, now + LIMITS.period),
+            Admission::Limited
+        );
+    }
+
+    #[test]
+    fn admit_keeps_the_usage_across_reconnects() {
+        let mut limiter = GossipLimiter::new(LIMITS, 2);
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        // Usage is not tied to the substream, a reconnecting peer is still limited.
+        assert_eq!(limiter.admit(peer, 11, now), Admission::Limited);
+        assert_eq!(limiter.admit(peer, 1, now), Admission::Dropped);
+
+        // Only the least recently active peers are forgotten.
+        assert_eq!(limiter.admit(PeerId::random(), 1, now), Admission::Admitted);
+        assert_eq!(limiter.admit(peer, 1, now), Admission::Dropped);
+        assert_eq!(limiter.admit(PeerId::random(), 1, now), Admission::Admitted);
+        assert_eq!(limiter.admit(PeerId::random(), 1, now), Admission::Admitted);
+        assert_eq!(limiter.admit(peer, 1, now), Admission::Admitted);
+    }
+
+    /// A peer store and the transactions protocol of the libp2p backend, limited by `LIMITS`.
+    fn rate_limited_transactions() -> (PeerStoreImpl, RateLimitedTransactions) {
+        let peer_store = PeerStoreImpl::new(Vec::new(), None);
+        let (_, service) = NetworkWorker::<Block, Hash>::notification_config(
+            "/transactions/1".into(),
+            Vec::new(),
+            1024,
+            None,
+            SetConfig::default(),
+            NotificationMetrics::new(None),
+            Arc::new(peer_store.handle()),
+        );
+        let service =
+            RateLimitedTransactions::new(service, LIMITS, Arc::new(peer_store.handle()), None);
+        (peer_store, service)
+    }
+
+    #[test]
+    fn peers_over_their_limit_in_three_periods_are_banned() {
+        let (peer_store, mut service) = rate_limited_transactions();
+        let peer = PeerId::random();
+        let over_limit = Compact(LIMITS.max_transactions + 1).encode();
+        let now = Instant::now();
+
+        for period in 0..2 {
+            let now = now + LIMITS.period * period;
+            assert!(!service.admit(peer, &over_limit, now));
+            assert!(!peer_store.handle().is_banned(&peer));
+        }
+        assert!(!service.admit(peer, &over_limit, now + LIMITS.period * 2));
+        assert!(peer_store.handle().is_banned(&peer));
+    }
+
+    #[test]
+    fn notifications_over_the_limit_lower_the_reputation() {
+        let (peer_store, mut service) = rate_limited_transactions();
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        assert!(service.admit(peer, &Compact(LIMITS.max_transactions).encode(), now));
+        assert_eq!(peer_store.handle().peer_reputation(&peer), 0);
+        assert!(!service.admit(peer, &Compact(1u32).encode(), now));
+        assert_eq!(
+            peer_store.handle().peer_reputation(&peer),
+            GOSSIP_LIMIT_EXCEEDED.value
+        );
+        assert!(!service.admit(peer, &Compact(1u32).encode(), now));
+        assert_eq!(
+            peer_store.handle().peer_reputation(&peer),
+            GOSSIP_LIMIT_EXCEEDED.value + GOSSIP_OVER_LIMIT.value
+        );
+    }
+
+    #[test]
+    fn transactions_protocol_is_rate_limited() {
+        let peer_store = PeerStoreImpl::new(Vec::new(), None);
+        assert!(transactions_protocol::<NetworkWorker<Block, Hash>>(
+            "test".into(),
+            Hash::default(),
+            None,
+            NotificationMetrics::new(None),
+            Arc::new(peer_store.handle()),
+            LIMITS,
+            None,
+        )
+        .is_ok());
+        assert!(GOSSIP_LIMITS.with_borrow(Option::is_none));
+    }
+
+    /// `build_network_advanced` follows `sc_service::build_network_advanced`, and
+    /// `transactions_protocol` the way `TransactionsHandlerPrototype::new` creates its protocol,
+    /// in these versions. Both must be compared with upstream again when these are upgraded.
+    #[test]
+    fn network_follows_the_forked_versions() {
+        let manifest = include_str!("../../Cargo.toml");
+        for (dependency, version) in [
+            ("sc-service", "0.50.0"),
+            ("sc-network-transactions", "0.48.0"),
+        ] {
+            assert!(
+                manifest
+                    .lines()
+                    .any(|line| line
+                        .starts_with(&format!("{dependency} = {{ version = \"{version}\""))),
+                "`build_network_advanced` must be compared with {dependency} {version}",
+            );
+        }
+    }
+}
@@ -5,6 +5,8 @@ mod benchmarking;
 mod chain_spec;
 mod cli;
 mod command;
+mod feeless;
+mod gossip;
 mod rpc;
 mod service;
 
@@ -7,19 +7,117 @@
 
 use std::sync::Arc;
 
-use jsonrpsee::RpcModule;
+use futures::{channel::mpsc, future, stream, StreamExt};
+use jsonrpsee::{
+    core::RpcResult,
+    proc_macros::rpc,
+    types::{error::ErrorObject, ErrorObjectOwned},
+    PendingSubscriptionSink, RpcModule,
+};
+use pallet_feeless_runtime_api::{FeelessApi as FeelessRuntimeApi, Quota};
+use sc_client_api::BlockchainEvents;
+use sc_consensus_manual_seal::{
+    rpc::{ManualSeal, ManualSealApiServer},
+    EngineCommand,
+};
+use sc_rpc::{
+    utils::{spawn_subscription_task, BoundedVecDeque, PendingSubscription},
+    SubscriptionTaskExecutor,
+};
 use sc_transaction_pool_api::TransactionPool;
-use solochain_template_runtime::{opaque::Block, AccountId, Balance, Nonce};
+use solochain_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Nonce};
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 
+/// Feeless RPC methods.
+#[rpc(server)]
+pub trait FeelessApi<BlockHash, AccountId, BlockNumber> {
+    /// Returns what is left of the quota of `who` at the given block, or the best block.
+    #[method(name = "feeless_quota")]
+    fn quota(&self, who: AccountId, at: Option<BlockHash>) -> RpcResult<Quota<BlockNumber>>;
+
+    /// Subscribes to the quota of `who`, sent at once and then on each new best block, which
+    /// includes the blocks where its period resets.
+    #[subscription(
+        name = "feeless_subscribeQuota" => "feeless_quotaUpdate",
+        unsubscribe = "feeless_unsubscribeQuota",
+        item = Quota<BlockNumber>
+    )]
+    fn subscribe_quota(&self, who: AccountId);
+}
+
+/// Implementation of [`FeelessApiServer`] reading quotas through the `FeelessApi` runtime API.
+pub struct Feeless<C> {
+    client: Arc<C>,
+    executor: SubscriptionTaskExecutor,
+}
+
+impl<C> Feeless<C> {
+    /// Creates the RPC handler.
+    pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+        Self { client, executor }
+    }
+}
+
+impl<C> FeelessApiServer<Hash, AccountId, BlockNumber> for Feeless<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block>,
+    C: Send + Sync + 'static,
+    C::Api: FeelessRuntimeApi<Block, AccountId, BlockNumber>,
+{
+    fn quota(&self, who: AccountId, at: Option<Hash>) -> RpcResult<Quota<BlockNumber>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        self.client
+            .runtime_api()
+            .quota(at, who)
+            .map_err(runtime_error)
+    }
+
+    fn subscribe_quota(&self, pending: PendingSubscriptionSink, who: AccountId) {
+        let quota_at = {
+            let client = self.client.clone();
+            move |at| {
+                client
+                    .runtime_api()
+                    .quota(at, who.clone())
+                    .map_err(|e| log::warn!("Failed to read the quota at {at}: {e}"))
+                    .ok()
+            }
+        };
+
+        // As for new heads in `sc_rpc`, a best block imported before the stream is set up is
+        // missed, and the next one catches up.
+        let current = quota_at(self.client.info().best_hash);
+        let updates = self
+            .client
+            .import_notification_stream()
+            .filter(|notification| future::ready(notification.is_new_best))
+            .filter_map(move |notification| future::ready(quota_at(notification.hash)));
+        let stream = stream::iter(current).chain(updates);
+
+        spawn_subscription_task(
+            &self.executor,
+            PendingSubscription::from(pending).pipe_from_stream(stream, BoundedVecDeque::default()),
+        );
+    }
+}
+
+/// Converts a runtime API error into an RPC error.
+fn runtime_error(err: impl std::fmt::Debug) -> ErrorObjectOwned {
+    ErrorObject::owned(1, "Runtime error", Some(format!("{err:?}")))
+}
+
 /// Full client dependencies.
 pub struct FullDeps<C, P> {
     /// The client instance to use.
     pub client: Arc<C>,
     /// Transaction pool instance.
     pub pool: Arc<P>,
+    /// Channel to the manual seal authoring task, when blocks are sealed manually.
+    pub command_sink: Option<mpsc::Sender<EngineCommand<Hash>>>,
+    /// Executor of the subscription tasks.
+    pub subscription_executor: SubscriptionTaskExecutor,
 }
 
 /// Instantiate all full RPC extensions.
@@ -29,9 +127,11 @@ pub fn create_full<C, P>(
 where
     C: ProvideRuntimeApi<Block>,
     C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+    C: BlockchainEvents<Block>,
     C: Send + Sync + 'static,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+    C::Api: FeelessRuntimeApi<Block, AccountId, BlockNumber>,
     C::Api: BlockBuilder<Block>,
     P: TransactionPool + 'static,
 {
@@ -39,22 +139,30 @@ where
     use substrate_frame_rpc_system::{System, SystemApiServer};
 
     let mut module = RpcModule::new(());
-    let FullDeps { client, pool } = deps;
+    let FullDeps {
+        client,
+        pool,
+        command_sink,
+        subscription_executor,
+    } = deps;
 
     module.merge(System::new(client.clone(), pool).into_rpc())?;
-    module.merge(TransactionPayment::new(client).into_rpc())?;
+    module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+    module.merge(Feeless::new(client, subscription_executor).into_rpc())?;
+
+    if let Some(command_sink) = command_sink {
+        // `engine_createBlock` and `engine_finalizeBlock`.
+        module.merge(ManualSeal::new(command_sink).into_rpc())?;
+    }
 
     // Extend this RPC with a custom API by using the following syntax.
     // `YourRpcStruct` should have a reference to a client, which is needed
     // to call into the runtime.
     // `module.merge(YourRpcTrait::into_rpc(YourRpcStruct::new(ReferenceToClient, ...)))?;`
 
-    // You probably want to enable the `rpc v2 chainSpec` API as well
-    //
-    // let chain_name = chain_spec.name().to_string();
-    // let genesis_hash = client.block_hash(0).ok().flatten().expect("Genesis block exists; qed");
-    // let properties = chain_spec.properties();
-    // module.merge(ChainSpec::new(chain_name, genesis_hash, properties).into_rpc())?;
+    // The RPC v2 groups of the new JSON-RPC spec (`chainSpec`, `chainHead`, `transaction` and,
+    // on archive nodes, `archive`) are already merged by `sc_service::spawn_tasks`: merging
+    // them here again would fail on duplicate method names.
 
     Ok(module)
 }
@@ -1,15 +1,26 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use futures::FutureExt;
+use crate::{cli::Sealing, gossip::GossipLimits};
+use futures::{channel::mpsc, stream, FutureExt, Stream, StreamExt};
 use sc_client_api::{Backend, BlockBackend};
+use sc_consensus::ImportQueue;
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 use sc_consensus_grandpa::SharedVoterState;
-use sc_service::{error::Error as ServiceError, Configuration, TaskManager, WarpSyncConfig};
+use sc_consensus_manual_seal::{
+    consensus::{aura::AuraConsensusDataProvider, timestamp::SlotTimestampProvider},
+    EngineCommand, ManualSealParams,
+};
+use sc_network_sync::service::network::NetworkServiceProvider;
+use sc_service::{
+    error::Error as ServiceError, BuildNetworkAdvancedParams, Configuration,
+    DefaultSyncingEngineConfig, TaskManager, WarpSyncConfig,
+};
 use sc_telemetry::{Telemetry, TelemetryWorker};
-use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sc_transaction_pool_api::{OffchainTransactionPoolFactory, TransactionPool};
 use solochain_template_runtime::{self, apis::RuntimeApi, opaque::Block};
+use sp_consensus::block_validation::DefaultBlockAnnounceValidator;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use std::{pin::Pin, sync::Arc, time::Duration};
 
 pub(crate) type FullClient = sc_service::TFullClient<
     Block,
@@ -23,6 +34,9 @@ type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 /// imported and generated.
 const GRANDPA_JUSTIFICATION_PERIOD: u32 = 512;
 
+/// The maximum number of rate-limited accounts remembered by the transaction pool pre-filter.
+const THROTTLE_CACHE_SIZE: u32 = 10_000;
+
 pub type Service = sc_service::PartialComponents<
     FullClient,
     FullBackend,
@@ -36,7 +50,10 @@ pub type Service = sc_service::PartialComponents<
     ),
 >;
 
-pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
+pub fn new_partial(
+    config: &Configuration,
+    sealing: Option<Sealing>,
+) -> Result<Service, ServiceError> {
     let telemetry = config
         .telemetry_endpoints
         .clone()
@@ -85,6 +102,26 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
         telemetry.as_ref().map(|x| x.handle()),
     )?;
 
+    // Manually sealed blocks carry no Aura seal, so they are imported without verification.
+    if sealing.is_some() {
+        let import_queue = sc_consensus_manual_seal::import_queue(
+            Box::new(client.clone()),
+            &task_manager.spawn_essential_handle(),
+            config.prometheus_registry(),
+        );
+
+        return Ok(sc_service::PartialComponents {
+            client,
+            backend,
+            task_manager,
+            import_queue,
+            keystore_container,
+            select_chain,
+            transaction_pool,
+            other: (grandpa_block_import, grandpa_link, telemetry),
+        });
+    }
+
     let cidp_client = client.clone();
     let import_queue =
         sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _>(ImportQueueParams {
@@ -129,10 +166,15 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
 }
 
 /// Builds a new service for a full client.
+///
+/// With `sealing`, blocks are sealed by `sc_consensus_manual_seal` instead of Aura and GRANDPA.
+/// Incoming transaction gossip is limited per peer by `gossip_limits`.
 pub fn new_full<
     N: sc_network::NetworkBackend<Block, <Block as sp_runtime::traits::Block>::Hash>,
 >(
     config: Configuration,
+    sealing: Option<Sealing>,
+    gossip_limits: GossipLimits,
 ) -> Result<TaskManager, ServiceError> {
     let sc_service::PartialComponents {
         client,
@@ -143,7 +185,14 @@ pub fn new_full<
         select_chain,
         transaction_pool,
         other: (block_import, grandpa_link, mut telemetry),
-    } = new_partial(&config)?;
+    } = new_partial(&config, sealing)?;
+
+    let transaction_pool = Arc::new(crate::feeless::FeelessPool::new(
+        transaction_pool,
+        client.clone(),
+        THROTTLE_CACHE_SIZE,
+        config.prometheus_registry(),
+    ));
 
     let mut net_config = sc_network::config::FullNetworkConfiguration::<
         Block,
@@ -161,13 +210,16 @@ pub fn new_full<
             .expect("Genesis block exists; qed"),
         &config.chain_spec,
     );
-    let (grandpa_protocol_config, grandpa_notification_service) =
-        sc_consensus_grandpa::grandpa_peers_set_config::<_, N>(
-            grandpa_protocol_name.clone(),
-            metrics.clone(),
-            peer_store_handle,
-        );
-    net_config.add_notification_protocol(grandpa_protocol_config);
+    let grandpa_notification_service = sealing.is_none().then(|| {
+        let (grandpa_protocol_config, grandpa_notification_service) =
+            sc_consensus_grandpa::grandpa_peers_set_config::<_, N>(
+                grandpa_protocol_name.clone(),
+                metrics.clone(),
+                peer_store_handle.clone(),
+            );
+        net_config.add_notification_protocol(grandpa_protocol_config);
+        grandpa_notification_service
+    });
 
     let warp_sync = Arc::new(sc_consensus_grandpa::warp_proof::NetworkProvider::new(
         backend.clone(),
@@ -175,20 +227,49 @@ pub fn new_full<
         Vec::default(),
     ));
 
-    let (network, system_rpc_tx, tx_handler_controller, sync_service) =
-        sc_service::build_network(sc_service::BuildNetworkParams {
-            config: &config,
-            net_config,
+    let network_service_provider = NetworkServiceProvider::new();
+    let (sync_service, block_announce_config) =
+        sc_service::build_default_syncing_engine(DefaultSyncingEngineConfig {
+            role: config.role,
+            protocol_id: config.protocol_id(),
+            fork_id: config.chain_spec.fork_id(),
+            net_config: &mut net_config,
+            block_announce_validator: Box::new(DefaultBlockAnnounceValidator),
+            network_service_handle: network_service_provider.handle(),
+            warp_sync_config: sealing
+                .is_none()
+                .then_some(WarpSyncConfig::WithProvider(warp_sync)),
             client: client.clone(),
-            transaction_pool: transaction_pool.clone(),
-            spawn_handle: task_manager.spawn_handle(),
-            import_queue,
-            block_announce_validator_builder: None,
-            warp_sync_config: Some(WarpSyncConfig::WithProvider(warp_sync)),
-            block_relay: None,
-            metrics,
+            import_queue_service: import_queue.service(),
+            num_peers_hint: config.network.default_peers_set.in_peers as usize
+                + config.network.default_peers_set.out_peers as usize,
+            spawn_handle: &task_manager.spawn_handle(),
+            metrics_registry: config.prometheus_registry(),
+            metrics: metrics.clone(),
         })?;
 
+    let (network, system_rpc_tx, tx_handler_controller, sync_service) =
+        crate::gossip::build_network_advanced(
+            BuildNetworkAdvancedParams {
+                role: config.role,
+                protocol_id: config.protocol_id(),
+                fork_id: config.chain_spec.fork_id(),
+                ipfs_server: config.network.ipfs_server,
+                announce_block: config.announce_block,
+                net_config,
+                client: client.clone(),
+                transaction_pool: transaction_pool.clone(),
+                spawn_handle: task_manager.spawn_handle(),
+                import_queue,
+                sync_service,
+                block_announce_config,
+                network_service_provider,
+                metrics_registry: config.prometheus_registry(),
+                metrics,
+            },
+            gossip_limits,
+        )?;
+
     if config.offchain_worker.enabled {
         let offchain_workers =
             sc_offchain::OffchainWorkers::new(sc_offchain::OffchainWorkerOptions {
@@ -216,17 +297,23 @@ pub fn new_full<
     let force_authoring = config.force_authoring;
     let backoff_authoring_blocks: Option<()> = None;
     let name = config.network.node_name.clone();
-    let enable_grandpa = !config.disable_grandpa;
+    let enable_grandpa = !config.disable_grandpa && sealing.is_none();
     let prometheus_registry = config.prometheus_registry().cloned();
 
+    // Commands of the `engine_*` RPCs to the manual seal authoring task.
+    let (command_sink, commands_stream) = mpsc::channel(1024);
+
     let rpc_extensions_builder = {
         let client = client.clone();
         let pool = transaction_pool.clone();
+        let command_sink = sealing.is_some().then_some(command_sink);
 
-        Box::new(move |_| {
+        Box::new(move |subscription_executor| {
             let deps = crate::rpc::FullDeps {
                 client: client.clone(),
                 pool: pool.clone(),
+                command_sink: command_sink.clone(),
+                subscription_executor,
             };
             crate::rpc::create_full(deps).map_err(Into::into)
         })
@@ -247,7 +334,70 @@ pub fn new_full<
         telemetry: telemetry.as_mut(),
     })?;
 
-    if role.is_authority() {
+    if let Some(sealing) = sealing {
+        let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+            task_manager.spawn_handle(),
+            client.clone(),
+            transaction_pool.clone(),
+            prometheus_registry.as_ref(),
+            telemetry.as_ref().map(|x| x.handle()),
+        );
+
+        // Sealed blocks are finalized at once, except in manual mode where
+        // `engine_finalizeBlock` does it.
+        let seal = |create_empty| EngineCommand::SealNewBlock {
+            create_empty,
+            finalize: true,
+            parent_hash: None,
+            sender: None,
+        };
+        let commands_stream: Pin<Box<dyn Stream<Item = _> + Send>> = match sealing {
+            Sealing::Instant => Box::pin(stream::select(
+                commands_stream,
+                transaction_pool
+                    .import_notification_stream()
+                    .map(move |_| seal(false)),
+            )),
+            Sealing::Manual => Box::pin(commands_stream),
+            Sealing::Interval(millis) => Box::pin(stream::select(
+                commands_stream,
+                stream::unfold((), move |()| async move {
+                    futures_timer::Delay::new(Duration::from_millis(millis)).await;
+                    Some((seal(true), ()))
+                }),
+            )),
+        };
+
+        let cidp_client = client.clone();
+        let manual_seal = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+            block_import: client.clone(),
+            env: proposer_factory,
+            client: client.clone(),
+            pool: transaction_pool.clone(),
+            commands_stream,
+            select_chain,
+            consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client))),
+            create_inherent_data_providers: move |_, ()| {
+                let cidp_client = cidp_client.clone();
+                async move {
+                    // Each block is one slot after its parent, whatever the wall clock says.
+                    let timestamp = SlotTimestampProvider::new_aura(cidp_client)?;
+                    let slot =
+                        sp_consensus_aura::inherents::InherentDataProvider::new(timestamp.slot());
+
+                    Ok((slot, timestamp))
+                }
+            },
+        });
+
+        // the manual seal authoring task is considered essential, i.e. if it
+        // fails we take down the service with it.
+        task_manager.spawn_essential_handle().spawn_blocking(
+            "manual-seal",
+            Some("block-authoring"),
+            manual_seal,
+        );
+    } else if role.is_authority() {
         let proposer_factory = sc_basic_authorship::ProposerFactory::new(
             task_manager.spawn_handle(),
             client.clone(),
@@ -327,7 +477,8 @@ pub fn new_full<
             link: grandpa_link,
             network,
             sync: Arc::new(sync_service),
-            notification_service: grandpa_notification_service,
+            notification_service: grandpa_notification_service
+                .expect("GRANDPA is disabled with manual sealing; qed"),
             voting_rule: sc_consensus_grandpa::VotingRulesBuilder::default().build(),
             prometheus_registry,
             shared_voter_state: SharedVoterState::empty(),
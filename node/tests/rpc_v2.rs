@@ -0,0 +1,245 @@
+//! Tests of the RPC v2 groups of the new JSON-RPC spec (`chainSpec`, `chainHead` and
+//! `transaction`) against a dev node sealing blocks on `engine_createBlock`.
+
+use codec::Encode;
+use jsonrpsee::{
+    core::client::{ClientT, Subscription, SubscriptionClientT},
+    rpc_params,
+    ws_client::{WsClient, WsClientBuilder},
+};
+use serde_json::Value;
+use solochain_template_runtime as runtime;
+use sp_core::{Pair, H256};
+use std::{
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    time::Duration,
+};
+
+/// Time for a debug build of the node to start its RPC server.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Dev node killed on drop.
+struct Node(Child);
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Starts a dev node with manual sealing and returns it with the URL of its RPC server.
+fn start_node() -> (Node, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_solochain-template-node"))
+        .args([
+            "--dev",
+            "--tmp",
+            "--sealing",
+            "manual",
+            "--no-prometheus",
+            "--no-telemetry",
+        ])
+        .args(["--rpc-port", "0", "--port", "0"])
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Node binary runs");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let node = Node(child);
+
+    // Keep draining the logs after the address is found, so that the node never blocks on a
+    // full pipe.
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if let Some((_, addrs)) = line.split_once("Running JSON-RPC server: addr=") {
+                let addr = addrs.split([',', ' ']).next().unwrap_or_default();
+                let _ = tx.send(format!("ws://{addr}"));
+            }
+        }
+    });
+    let url = rx.recv_timeout(STARTUP_TIMEOUT).expect("RPC server starts");
+
+    (node, url)
+}
+
+async fn create_block(client: &WsClient) -> H256 {
+    let created: Value = client
+        .request(
+            "engine_createBlock",
+            rpc_params![true, true, Option::<H256>::None],
+        )
+        .await
+        .unwrap();
+    serde_json::from_value(created["hash"].clone()).unwrap()
+}
+
+/// Returns the next notification of `subscription`.
+async fn next(subscription: &mut Subscription<Value>) -> Value {
+    tokio::time::timeout(Duration::from_secs(60), subscription.next())
+        .await
+        .expect("Notification is received")
+        .expect("Subscription is open")
+        .unwrap()
+}
+
+/// Signs an immortal `System::remark` of Alice.
+fn remark(genesis: H256, nonce: u32) -> Vec<u8> {
+    let call = runtime::RuntimeCall::System(frame_system::Call::remark {
+        remark: b"rpc v2".to_vec(),
+    });
+    let tx_ext: runtime::TxExtension = (
+        frame_system::CheckNonZeroSender::<runtime::Runtime>::new(),
+        frame_system::CheckSpecVersion::<runtime::Runtime>::new(),
+        frame_system::CheckTxVersion::<runtime::Runtime>::new(),
+        frame_system::CheckGenesis::<runtime::Runtime>::new(),
+        frame_system::CheckEra::<runtime::Runtime>::from(sp_runtime::generic::Era::Immortal),
+        pallet_feeless::CheckRate::<runtime::Runtime>::new(),
+        frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
+        frame_system::CheckWeight::<runtime::Runtime>::new(),
+        pallet_transaction_payment::ChargeTransactionPayment::<runtime::Runtime>::from(0),
+        frame_metadata_hash_extension::CheckMetadataHash::<runtime::Runtime>::new(false),
+    );
+    let raw_payload = runtime::SignedPayload::from_raw(
+        call.clone(),
+        tx_ext.clone(),
+        (
+            (),
+            runtime::VERSION.spec_version,
+            runtime::VERSION.transaction_version,
+            genesis,
+            genesis,
+            (),
+            (),
+            (),
+            (),
+            None,
+        ),
+    );
+    let sender = sp_keyring::Sr25519Keyring::Alice.pair();
+    let signature = raw_payload.using_encoded(|e| sender.sign(e));
+
+    runtime::UncheckedExtrinsic::new_signed(
+        call,
+        sp_runtime::AccountId32::from(sender.public()).into(),
+        runtime::Signature::Sr25519(signature),
+        tx_ext,
+    )
+    .encode()
+}
+
+async fn chain_spec_works(client: &WsClient) {
+    let name: String = client
+        .request("chainSpec_v1_chainName", rpc_params![])
+        .await
+        .unwrap();
+    assert_eq!(name, "Development");
+
+    let genesis: H256 = client
+        .request("chainSpec_v1_genesisHash", rpc_params![])
+        .await
+        .unwrap();
+    let block_zero: H256 = client
+        .request("chain_getBlockHash", rpc_params![0])
+        .await
+        .unwrap();
+    assert_eq!(genesis, block_zero);
+
+    let properties: Value = client
+        .request("chainSpec_v1_properties", rpc_params![])
+        .await
+        .unwrap();
+    assert!(properties.is_object());
+}
+
+async fn chain_head_works(client: &WsClient) {
+    let mut follow: Subscription<Value> = client
+        .subscribe(
+            "chainHead_v1_follow",
+            rpc_params![false],
+            "chainHead_v1_unfollow",
+        )
+        .await
+        .unwrap();
+    let initialized = next(&mut follow).await;
+    assert_eq!(initialized["event"], "initialized");
+    let finalized_head = initialized["finalizedBlockHashes"]
+        .as_array()
+        .and_then(|hashes| hashes.last())
+        .cloned()
+        .unwrap();
+
+    let hash = serde_json::to_value(create_block(client).await).unwrap();
+
+    let new_block = next(&mut follow).await;
+    assert_eq!(new_block["event"], "newBlock");
+    assert_eq!(new_block["blockHash"], hash);
+    assert_eq!(new_block["parentBlockHash"], finalized_head);
+
+    let best_block = next(&mut follow).await;
+    assert_eq!(best_block["event"], "bestBlockChanged");
+    assert_eq!(best_block["bestBlockHash"], hash);
+
+    let finalized = next(&mut follow).await;
+    assert_eq!(finalized["event"], "finalized");
+    assert_eq!(finalized["finalizedBlockHashes"], serde_json::json!([hash]));
+
+    // The header of a followed block is available until it is unpinned.
+    let subscription = follow.kind().clone();
+    let jsonrpsee::core::client::SubscriptionKind::Subscription(id) = subscription else {
+        panic!("chainHead_v1_follow is a subscription");
+    };
+    let header: Option<String> = client
+        .request("chainHead_v1_header", rpc_params![id, hash])
+        .await
+        .unwrap();
+    assert!(header.is_some());
+}
+
+async fn transaction_works(client: &WsClient) {
+    let genesis: H256 = client
+        .request("chainSpec_v1_genesisHash", rpc_params![])
+        .await
+        .unwrap();
+    let xt = sp_core::Bytes(remark(genesis, 0));
+    let mut watch: Subscription<Value> = client
+        .subscribe(
+            "transactionWatch_v1_submitAndWatch",
+            rpc_params![xt],
+            "transactionWatch_v1_unwatch",
+        )
+        .await
+        .unwrap();
+    assert_eq!(next(&mut watch).await["event"], "validated");
+
+    let hash = serde_json::to_value(create_block(client).await).unwrap();
+
+    let included = next(&mut watch).await;
+    assert_eq!(included["event"], "bestChainBlockIncluded");
+    assert_eq!(included["block"]["hash"], hash);
+    let finalized = next(&mut watch).await;
+    assert_eq!(finalized["event"], "finalized");
+    assert_eq!(finalized["block"]["hash"], hash);
+
+    // The operation id of a broadcast can be used to stop it.
+    let xt = sp_core::Bytes(remark(genesis, 1));
+    let operation: Option<String> = client
+        .request("transaction_v1_broadcast", rpc_params![xt])
+        .await
+        .unwrap();
+    let () = client
+        .request("transaction_v1_stop", rpc_params![operation.unwrap()])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn rpc_v2_groups_work() {
+    let (_node, url) = start_node();
+    let client = WsClientBuilder::default().build(&url).await.unwrap();
+
+    chain_spec_works(&client).await;
+    chain_head_works(&client).await;
+    transaction_works(&client).await;
+}
@@ -0,0 +1,85 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+use super::*;
+use crate::Pallet;
+use frame_benchmarking::v2::*;
+use frame_support::traits::fungible::Inspect;
+use frame_system::RawOrigin;
+
+/// A sorted schedule of `n` steps, each minting well above the existential deposit.
+fn schedule<T: Config>(n: u32) -> ScheduleOf<T> {
+    let reward = T::Currency::minimum_balance().saturating_mul(100u32.into());
+    (0..n)
+        .map(|i| IssuanceStep {
+            from: i.into(),
+            reward,
+        })
+        .collect::<Vec<_>>()
+        .try_into()
+        .expect("at most `MaxScheduleSteps` steps; qed")
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn set_schedule(n: Linear<0, { T::MaxScheduleSteps::get() }>) {
+        let schedule = schedule::<T>(n);
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, schedule.clone());
+
+        assert_eq!(Schedule::<T>::get(), schedule);
+    }
+
+    #[benchmark]
+    fn set_treasury_cut() {
+        #[extrinsic_call]
+        _(RawOrigin::Root, Perbill::from_percent(50));
+
+        assert_eq!(TreasuryCut::<T>::get(), Perbill::from_percent(50));
+    }
+
+    // Worst case: a full schedule read, and two accounts created.
+    #[benchmark]
+    fn reward() {
+        let max = T::MaxScheduleSteps::get();
+        Schedule::<T>::put(schedule::<T>(max));
+        TreasuryCut::<T>::put(Perbill::from_percent(50));
+        let author: T::AccountId = account("author", 0, 0);
+
+        #[block]
+        {
+            Pallet::<T>::reward(&author, max.into());
+        }
+
+        assert!(!T::Currency::balance(&author).is_zero());
+        assert!(!T::Currency::balance(&T::TreasuryAccount::get()).is_zero());
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+}
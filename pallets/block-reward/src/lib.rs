@@ -0,0 +1,255 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![doc = include_str!("../README.md")]
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::{Decode, DecodeWithMemTracking, Encode, MaxEncodedLen};
+use frame_support::{
+    ensure,
+    pallet_prelude::{BoundedVec, EnsureOrigin, IsType, StorageValue, ValueQuery},
+    traits::{fungible::Mutate, tokens::Balance, BuildGenesisConfig, FindAuthor, Get, Hooks},
+    weights::Weight,
+};
+use frame_system::pallet_prelude::{BlockNumberFor, OriginFor};
+pub use pallet::*;
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+    traits::{MaybeSerializeDeserialize, Saturating, Zero},
+    DispatchResult, Perbill, RuntimeDebug,
+};
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// A step of the issuance schedule: every block from `from` mints `reward`, until the next step.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    PartialEq,
+    Eq,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+    Serialize,
+    Deserialize,
+)]
+pub struct IssuanceStep<BlockNumber, Balance> {
+    /// First block of the step.
+    pub from: BlockNumber,
+    /// Issuance of each block of the step.
+    pub reward: Balance,
+}
+
+// The call enum expanded by `#[pallet::call]` trips these lints on the pinned toolchain.
+#[allow(unreachable_patterns, clippy::multiple_bound_locations)]
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    /// The issuance schedule, sorted by first block.
+    pub type ScheduleOf<T> = BoundedVec<
+        IssuanceStep<BlockNumberFor<T>, <T as Config>::Balance>,
+        <T as Config>::MaxScheduleSteps,
+    >;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching runtime event type.
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        /// The balance type of `Currency`.
+        type Balance: Balance + MaybeSerializeDeserialize;
+        /// The currency in which rewards are minted.
+        type Currency: Mutate<Self::AccountId, Balance = Self::Balance>;
+        /// Resolves the author of the block being built from its pre-runtime digests.
+        type FindAuthor: FindAuthor<Self::AccountId>;
+        /// The account receiving the `TreasuryCut` of every reward.
+        type TreasuryAccount: Get<Self::AccountId>;
+        /// The origin which may change the issuance schedule and the treasury cut.
+        type RewardOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Maximum number of steps in `Schedule`.
+        type MaxScheduleSteps: Get<u32>;
+        /// A type representing the weights required by the dispatchables of this pallet.
+        type WeightInfo: WeightInfo;
+    }
+
+    /// The issuance schedule. Blocks before the first step are not rewarded.
+    #[pallet::storage]
+    pub type Schedule<T: Config> = StorageValue<_, ScheduleOf<T>, ValueQuery>;
+
+    /// Share of every reward paid to `TreasuryAccount` instead of the block author.
+    #[pallet::storage]
+    pub type TreasuryCut<T: Config> = StorageValue<_, Perbill, ValueQuery>;
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+            let digest = frame_system::Pallet::<T>::digest();
+            let pre_runtime_digests = digest.logs().iter().filter_map(|d| d.as_pre_runtime());
+            if let Some(author) = T::FindAuthor::find_author(pre_runtime_digests) {
+                Self::reward(&author, n);
+            }
+            T::WeightInfo::reward()
+        }
+    }
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Initial issuance schedule, sorted by first block.
+        pub schedule: Vec<IssuanceStep<BlockNumberFor<T>, T::Balance>>,
+        /// Initial treasury cut.
+        pub treasury_cut: Perbill,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            let schedule: ScheduleOf<T> = self
+                .schedule
+                .clone()
+                .try_into()
+                .expect("the genesis schedule has more than `MaxScheduleSteps` steps");
+            assert!(
+                Pallet::<T>::is_sorted(&schedule),
+                "the genesis schedule is not sorted by first block"
+            );
+            Schedule::<T>::put(schedule);
+            TreasuryCut::<T>::put(self.treasury_cut);
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// The block author was rewarded.
+        AuthorRewarded {
+            author: T::AccountId,
+            amount: T::Balance,
+        },
+        /// The treasury received its cut of the block reward.
+        TreasuryFunded { amount: T::Balance },
+        /// The issuance schedule changed.
+        ScheduleSet { steps: u32 },
+        /// The treasury cut changed.
+        TreasuryCutSet { cut: Perbill },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The schedule steps are not strictly increasing by first block.
+        UnsortedSchedule,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Replaces the issuance schedule.
+        ///
+        /// ## Arguments:
+        /// - `origin`: Must be `RewardOrigin`.
+        /// - `schedule`: The steps of the new schedule, strictly increasing by first block.
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::set_schedule(schedule.len() as u32))]
+        pub fn set_schedule(origin: OriginFor<T>, schedule: ScheduleOf<T>) -> DispatchResult {
+            T::RewardOrigin::ensure_origin(origin)?;
+            ensure!(Self::is_sorted(&schedule), Error::<T>::UnsortedSchedule);
+            let steps = schedule.len() as u32;
+            Schedule::<T>::put(schedule);
+            Self::deposit_event(Event::ScheduleSet { steps });
+            Ok(())
+        }
+
+        /// Sets the share of every reward paid to the treasury.
+        ///
+        /// ## Arguments:
+        /// - `origin`: Must be `RewardOrigin`.
+        /// - `cut`: The new treasury cut.
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::set_treasury_cut())]
+        pub fn set_treasury_cut(origin: OriginFor<T>, cut: Perbill) -> DispatchResult {
+            T::RewardOrigin::ensure_origin(origin)?;
+            TreasuryCut::<T>::put(cut);
+            Self::deposit_event(Event::TreasuryCutSet { cut });
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// The issuance of block `n`, from the last step starting at or before it.
+    pub fn reward_at(n: BlockNumberFor<T>) -> T::Balance {
+        Schedule::<T>::get()
+            .iter()
+            .rev()
+            .find(|step| step.from <= n)
+            .map_or_else(Zero::zero, |step| step.reward)
+    }
+
+    /// Mints the issuance of block `n`, split between `author` and the treasury.
+    ///
+    /// A share that cannot be minted, for instance because it would create an account below the
+    /// existential deposit, is not issued.
+    pub fn reward(author: &T::AccountId, n: BlockNumberFor<T>) {
+        let reward = Self::reward_at(n);
+        if reward.is_zero() {
+            return;
+        }
+        let treasury_share = TreasuryCut::<T>::get().mul_floor(reward);
+        let author_share = reward.saturating_sub(treasury_share);
+        if !treasury_share.is_zero()
+            && T::Currency::mint_into(&T::TreasuryAccount::get(), treasury_share).is_ok()
+        {
+            Self::deposit_event(Event::TreasuryFunded {
+                amount: treasury_share,
+            });
+        }
+        if !author_share.is_zero() && T::Currency::mint_into(author, author_share).is_ok() {
+            Self::deposit_event(Event::AuthorRewarded {
+                author: author.clone(),
+                amount: author_share,
+            });
+        }
+    }
+
+    fn is_sorted(schedule: &[IssuanceStep<BlockNumberFor<T>, T::Balance>]) -> bool {
+        schedule.windows(2).all(|w| w[0].from < w[1].from)
+    }
+}
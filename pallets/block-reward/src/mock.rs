@@ -0,0 +1,139 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+use crate as pallet;
+use frame_support::{
+    derive_impl,
+    traits::{ConstU32, ConstU64, FindAuthor},
+    ConsensusEngineId,
+};
+use frame_system::EnsureRoot;
+use sp_runtime::{traits::parameter_types, BuildStorage, Perbill};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+#[frame_support::runtime]
+mod runtime {
+    // The main runtime
+    #[runtime::runtime]
+    // Runtime Types to be generated
+    #[runtime::derive(
+        RuntimeCall,
+        RuntimeEvent,
+        RuntimeError,
+        RuntimeOrigin,
+        RuntimeFreezeReason,
+        RuntimeHoldReason,
+        RuntimeSlashReason,
+        RuntimeLockId,
+        RuntimeTask
+    )]
+    pub struct Test;
+
+    #[runtime::pallet_index(0)]
+    pub type System = frame_system::Pallet<Test>;
+
+    #[runtime::pallet_index(1)]
+    pub type BlockReward = pallet::Pallet<Test>;
+
+    #[runtime::pallet_index(2)]
+    pub type Balances = pallet_balances::Pallet<Test>;
+}
+
+pub const AUTHOR: u64 = 1;
+pub const TREASURY: u64 = 100;
+
+parameter_types! {
+    pub const TreasuryAccount: u64 = TREASURY;
+}
+
+frame_support::parameter_types! {
+    /// The author found by `FindAuthorFromStatic`.
+    pub static BlockAuthor: Option<u64> = Some(AUTHOR);
+}
+
+/// Finds `BlockAuthor` whatever the digests.
+pub struct FindAuthorFromStatic;
+impl FindAuthor<u64> for FindAuthorFromStatic {
+    fn find_author<'a, I>(_digests: I) -> Option<u64>
+    where
+        I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+    {
+        BlockAuthor::get()
+    }
+}
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+    type AccountData = pallet_balances::AccountData<u64>;
+    type Block = Block;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+    type AccountStore = System;
+    type ExistentialDeposit = ConstU64<5>;
+}
+
+impl pallet::Config for Test {
+    type Balance = u64;
+    type Currency = Balances;
+    type FindAuthor = FindAuthorFromStatic;
+    type MaxScheduleSteps = ConstU32<4>;
+    type RewardOrigin = EnsureRoot<Self::AccountId>;
+    type RuntimeEvent = RuntimeEvent;
+    type TreasuryAccount = TreasuryAccount;
+    type WeightInfo = ();
+}
+
+// Build genesis storage according to the mock runtime: 100 per block from block 1 and 50 from
+// block 10, with a 20% treasury cut.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(AUTHOR, 10)],
+        ..Default::default()
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    pallet::GenesisConfig::<Test> {
+        schedule: vec![
+            pallet::IssuanceStep {
+                from: 1,
+                reward: 100,
+            },
+            pallet::IssuanceStep {
+                from: 10,
+                reward: 50,
+            },
+        ],
+        treasury_cut: Perbill::from_percent(20),
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    storage.into()
+}
@@ -0,0 +1,203 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+use crate::{mock::*, Error, Event, IssuanceStep, Schedule, TreasuryCut};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{fungible::Inspect, ConstU32, Hooks},
+    BoundedVec,
+};
+use frame_system::RawOrigin;
+use sp_runtime::{DispatchError, Perbill};
+
+/// Initializes block `n` as the block reward hook sees it.
+fn initialize(n: u64) {
+    System::set_block_number(n);
+    BlockReward::on_initialize(n);
+}
+
+fn schedule(steps: &[(u64, u64)]) -> BoundedVec<IssuanceStep<u64, u64>, ConstU32<4>> {
+    steps
+        .iter()
+        .map(|&(from, reward)| IssuanceStep { from, reward })
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn genesis_config_works() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Schedule::<Test>::get(), schedule(&[(1, 100), (10, 50)]));
+        assert_eq!(TreasuryCut::<Test>::get(), Perbill::from_percent(20));
+    })
+}
+
+#[test]
+fn rewards_author_and_treasury() {
+    new_test_ext().execute_with(|| {
+        let issuance = Balances::total_issuance();
+        initialize(1);
+        assert_eq!(Balances::balance(&AUTHOR), 10 + 80);
+        assert_eq!(Balances::balance(&TREASURY), 20);
+        assert_eq!(Balances::total_issuance(), issuance + 100);
+        System::assert_has_event(Event::TreasuryFunded { amount: 20 }.into());
+        System::assert_last_event(
+            Event::AuthorRewarded {
+                author: AUTHOR,
+                amount: 80,
+            }
+            .into(),
+        );
+
+        initialize(2);
+        assert_eq!(Balances::balance(&AUTHOR), 10 + 160);
+        assert_eq!(Balances::balance(&TREASURY), 40);
+    })
+}
+
+#[test]
+fn reward_follows_the_schedule() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(BlockReward::reward_at(0), 0);
+        assert_eq!(BlockReward::reward_at(1), 100);
+        assert_eq!(BlockReward::reward_at(9), 100);
+        assert_eq!(BlockReward::reward_at(10), 50);
+        assert_eq!(BlockReward::reward_at(u64::MAX), 50);
+
+        let issuance = Balances::total_issuance();
+        initialize(0);
+        assert_eq!(Balances::total_issuance(), issuance);
+        initialize(10);
+        assert_eq!(Balances::total_issuance(), issuance + 50);
+        System::assert_last_event(
+            Event::AuthorRewarded {
+                author: AUTHOR,
+                amount: 40,
+            }
+            .into(),
+        );
+    })
+}
+
+#[test]
+fn no_reward_without_author() {
+    new_test_ext().execute_with(|| {
+        BlockAuthor::set(None);
+        let issuance = Balances::total_issuance();
+        initialize(1);
+        assert_eq!(Balances::total_issuance(), issuance);
+        assert!(System::events().is_empty());
+    })
+}
+
+#[test]
+fn shares_below_existential_deposit_are_not_issued() {
+    new_test_ext().execute_with(|| {
+        // The treasury share of 2 cannot create the treasury account.
+        assert_ok!(BlockReward::set_schedule(
+            RawOrigin::Root.into(),
+            schedule(&[(0, 10)])
+        ));
+        System::reset_events();
+        initialize(1);
+        assert_eq!(Balances::balance(&TREASURY), 0);
+        assert_eq!(Balances::balance(&AUTHOR), 10 + 8);
+        System::assert_last_event(
+            Event::AuthorRewarded {
+                author: AUTHOR,
+                amount: 8,
+            }
+            .into(),
+        );
+        assert!(!System::events().iter().any(|record| matches!(
+            record.event,
+            RuntimeEvent::BlockReward(Event::TreasuryFunded { .. })
+        )));
+    })
+}
+
+#[test]
+fn set_schedule_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            BlockReward::set_schedule(RawOrigin::Signed(AUTHOR).into(), schedule(&[])),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            BlockReward::set_schedule(RawOrigin::Root.into(), schedule(&[(5, 1), (5, 2)])),
+            Error::<Test>::UnsortedSchedule
+        );
+        assert_noop!(
+            BlockReward::set_schedule(RawOrigin::Root.into(), schedule(&[(5, 1), (2, 2)])),
+            Error::<Test>::UnsortedSchedule
+        );
+
+        assert_ok!(BlockReward::set_schedule(
+            RawOrigin::Root.into(),
+            schedule(&[(0, 7), (5, 0)])
+        ));
+        System::assert_last_event(Event::ScheduleSet { steps: 2 }.into());
+        assert_eq!(BlockReward::reward_at(4), 7);
+        assert_eq!(BlockReward::reward_at(5), 0);
+
+        // An empty schedule stops the issuance.
+        assert_ok!(BlockReward::set_schedule(
+            RawOrigin::Root.into(),
+            schedule(&[])
+        ));
+        assert_eq!(BlockReward::reward_at(1), 0);
+    })
+}
+
+#[test]
+fn set_treasury_cut_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            BlockReward::set_treasury_cut(
+                RawOrigin::Signed(AUTHOR).into(),
+                Perbill::from_percent(50)
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(BlockReward::set_treasury_cut(
+            RawOrigin::Root.into(),
+            Perbill::one()
+        ));
+        System::assert_last_event(
+            Event::TreasuryCutSet {
+                cut: Perbill::one(),
+            }
+            .into(),
+        );
+
+        initialize(2);
+        assert_eq!(Balances::balance(&AUTHOR), 10);
+        assert_eq!(Balances::balance(&TREASURY), 100);
+        System::assert_last_event(Event::TreasuryFunded { amount: 100 }.into());
+    })
+}
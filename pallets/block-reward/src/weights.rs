@@ -0,0 +1,121 @@
+
+//! Autogenerated weights for `pallet_block_reward`
+//!
+//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 47.0.0
+//! DATE: 2026-10-18, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
+//! WORST CASE MAP SIZE: `1000000`
+//! HOSTNAME: `vm`, CPU: `Intel(R) Xeon(R) Processor`
+//! WASM-EXECUTION: `Compiled`, CHAIN: `Some("dev")`, DB CACHE: `1024`
+
+// Executed Command:
+// ./target/release/solochain-template-node
+// benchmark
+// pallet
+// --chain=dev
+// --pallet=pallet_block_reward
+// --extrinsic=*
+// --steps=50
+// --repeat=20
+// --wasm-execution=compiled
+// --output=pallets/block-reward/src/weights.rs
+// --template=.maintain/frame-weight-template.hbs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for `pallet_block_reward`.
+pub trait WeightInfo {
+	fn set_schedule(n: u32, ) -> Weight;
+	fn set_treasury_cut() -> Weight;
+	fn reward() -> Weight;
+}
+
+/// Weights for `pallet_block_reward` using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: `BlockReward::Schedule` (r:0 w:1)
+	/// Proof: `BlockReward::Schedule` (`max_values`: Some(1), `max_size`: Some(321), added: 816, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[0, 16]`.
+	fn set_schedule(_n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 8_243_000 picoseconds.
+		Weight::from_parts(12_944_036, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `BlockReward::TreasuryCut` (r:0 w:1)
+	/// Proof: `BlockReward::TreasuryCut` (`max_values`: Some(1), `max_size`: Some(4), added: 499, mode: `MaxEncodedLen`)
+	fn set_treasury_cut() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 11_984_000 picoseconds.
+		Weight::from_parts(13_928_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `BlockReward::Schedule` (r:1 w:0)
+	/// Proof: `BlockReward::Schedule` (`max_values`: Some(1), `max_size`: Some(321), added: 816, mode: `MaxEncodedLen`)
+	/// Storage: `BlockReward::TreasuryCut` (r:1 w:0)
+	/// Proof: `BlockReward::TreasuryCut` (`max_values`: Some(1), `max_size`: Some(4), added: 499, mode: `MaxEncodedLen`)
+	/// Storage: `System::Account` (r:2 w:2)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	/// Storage: `Account::PendingStatus` (r:2 w:0)
+	/// Proof: `Account::PendingStatus` (`max_values`: None, `max_size`: Some(50), added: 2525, mode: `MaxEncodedLen`)
+	fn reward() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `417`
+		//  Estimated: `6224`
+		// Minimum execution time: 78_781_000 picoseconds.
+		Weight::from_parts(97_594_000, 6224)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+	/// Storage: `BlockReward::Schedule` (r:0 w:1)
+	/// Proof: `BlockReward::Schedule` (`max_values`: Some(1), `max_size`: Some(321), added: 816, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[0, 16]`.
+	fn set_schedule(_n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 8_243_000 picoseconds.
+		Weight::from_parts(12_944_036, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `BlockReward::TreasuryCut` (r:0 w:1)
+	/// Proof: `BlockReward::TreasuryCut` (`max_values`: Some(1), `max_size`: Some(4), added: 499, mode: `MaxEncodedLen`)
+	fn set_treasury_cut() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 11_984_000 picoseconds.
+		Weight::from_parts(13_928_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `BlockReward::Schedule` (r:1 w:0)
+	/// Proof: `BlockReward::Schedule` (`max_values`: Some(1), `max_size`: Some(321), added: 816, mode: `MaxEncodedLen`)
+	/// Storage: `BlockReward::TreasuryCut` (r:1 w:0)
+	/// Proof: `BlockReward::TreasuryCut` (`max_values`: Some(1), `max_size`: Some(4), added: 499, mode: `MaxEncodedLen`)
+	/// Storage: `System::Account` (r:2 w:2)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	/// Storage: `Account::PendingStatus` (r:2 w:0)
+	/// Proof: `Account::PendingStatus` (`max_values`: None, `max_size`: Some(50), added: 2525, mode: `MaxEncodedLen`)
+	fn reward() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `417`
+		//  Estimated: `6224`
+		// Minimum execution time: 78_781_000 picoseconds.
+		Weight::from_parts(97_594_000, 6224)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+}
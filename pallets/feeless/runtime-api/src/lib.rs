@@ -0,0 +1,48 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+//! Runtime API definition for the feeless pallet.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_feeless::{Quota, UsageStats};
+use sp_runtime::traits::Block as BlockT;
+
+sp_api::decl_runtime_apis! {
+    /// API to query the rate limits of `pallet_feeless`.
+    pub trait FeelessApi<AccountId, BlockNumber>
+    where
+        AccountId: Codec,
+        BlockNumber: Codec,
+    {
+        /// Returns what is left of the quota of `who` for the next block.
+        fn quota(who: AccountId) -> Quota<BlockNumber>;
+        /// Returns the usage statistics of the last finalized block.
+        fn last_block_usage() -> UsageStats;
+        /// Returns whether `CheckRate` lets `xt` through without counting it, whatever the quota
+        /// of its signer.
+        fn is_exempt(xt: <Block as BlockT>::Extrinsic) -> bool;
+    }
+}
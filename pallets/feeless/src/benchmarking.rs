@@ -25,30 +25,248 @@
 // If not, see <http://www.gnu.org/licenses/>.
 use super::*;
 use crate::Pallet;
+use alloc::vec;
 use frame_benchmarking::v2::*;
+use frame_support::dispatch::{DispatchInfo, PostDispatchInfo};
 use frame_system::RawOrigin;
+use sp_runtime::traits::{AsTransactionAuthorizedOrigin, DispatchTransaction, Dispatchable};
+
+const SEED: u32 = 0;
 
 #[benchmarks(
     where
         T: frame_system::Config<AccountData = AccountData<T::Balance, BlockNumberFor<T>>>
             + Config
-            + pallet_balances::Config,)
+            + pallet_balances::Config
+            + Send
+            + Sync,
+        T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+        <T::RuntimeCall as Dispatchable>::RuntimeOrigin: AsTransactionAuthorizedOrigin,)
 ]
 mod benchmarks {
     use super::*;
 
     #[benchmark]
     fn set_status() {
-        let caller: T::AccountId = whitelisted_caller();
-        frame_system::Account::<T>::mutate(&caller, |_| {}); // Init AccountData
+        let who: T::AccountId = account("who", 0, SEED);
+        frame_system::Account::<T>::mutate(&who, |_| {}); // Init AccountData
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, who.clone(), crate::Status::Unlimited);
+
+        assert_eq!(
+            frame_system::Account::<T>::get(&who).data.rate.status,
+            crate::Status::Unlimited
+        );
+    }
+
+    #[benchmark]
+    fn set_status_batch(n: Linear<1, { T::MaxStatusBatch::get() }>) {
+        // Accounts that do not exist yet are the worst case, as their status is written to
+        // `PendingStatus` on top of the `System::Account` lookup.
+        let statuses: BoundedVec<_, T::MaxStatusBatch> = (0..n)
+            .map(|i| (account("who", i, SEED), crate::Status::Unlimited))
+            .collect::<Vec<(T::AccountId, _)>>()
+            .try_into()
+            .unwrap();
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, statuses);
+
+        frame_system::Pallet::<T>::assert_last_event(
+            <T as Config>::RuntimeEvent::from(Event::StatusBatchSet {
+                updated: 0,
+                pending: n,
+            })
+            .into(),
+        );
+    }
+
+    #[benchmark]
+    fn add_registrar() {
+        let registrar: T::AccountId = account("registrar", 0, SEED);
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Root,
+            registrar.clone(),
+            1,
+            crate::Status::Unlimited,
+        );
+
+        assert!(Registrars::<T>::contains_key(&registrar));
+    }
+
+    #[benchmark]
+    fn remove_registrar() {
+        let registrar: T::AccountId = account("registrar", 0, SEED);
+        Registrars::<T>::insert(
+            &registrar,
+            Registrar {
+                allowance: 1,
+                max_status: crate::Status::Unlimited,
+            },
+        );
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, registrar.clone());
+
+        assert!(!Registrars::<T>::contains_key(&registrar));
+    }
+
+    #[benchmark]
+    fn registrar_set_status() {
+        let registrar: T::AccountId = account("registrar", 0, SEED);
+        Registrars::<T>::insert(
+            &registrar,
+            Registrar {
+                allowance: 1,
+                max_status: crate::Status::Unlimited,
+            },
+        );
+        let who: T::AccountId = account("who", 0, SEED);
+        frame_system::Account::<T>::mutate(&who, |_| {}); // Init AccountData
 
         #[extrinsic_call]
-        _(RawOrigin::Root, caller.clone(), crate::Status::Unlimited);
+        _(
+            RawOrigin::Signed(registrar.clone()),
+            who.clone(),
+            crate::Status::Unlimited,
+        );
 
         assert_eq!(
-            frame_system::Account::<T>::get(&caller).data.rate.status,
+            frame_system::Account::<T>::get(&who).data.rate.status,
             crate::Status::Unlimited
         );
+        assert_eq!(Registrars::<T>::get(&registrar).unwrap().allowance, 0);
+    }
+
+    #[benchmark]
+    fn set_stamp_difficulty() {
+        #[extrinsic_call]
+        _(RawOrigin::Root, 16);
+
+        assert_eq!(StampDifficulty::<T>::get(), 16);
+    }
+
+    #[benchmark]
+    fn add_issuer() {
+        let issuer: T::AccountId = account("issuer", 0, SEED);
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, issuer.clone(), u8::MAX);
+
+        assert_eq!(Issuers::<T>::get(&issuer), Some(u8::MAX));
+    }
+
+    #[benchmark]
+    fn remove_issuer() {
+        let issuer: T::AccountId = account("issuer", 0, SEED);
+        Issuers::<T>::insert(&issuer, u8::MAX);
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, issuer.clone());
+
+        assert!(!Issuers::<T>::contains_key(&issuer));
+    }
+
+    #[benchmark]
+    fn claim_status() {
+        let issuer = T::BenchmarkHelper::issuer();
+        Issuers::<T>::insert(&issuer, u8::MAX);
+        let who: T::AccountId = account("who", 0, SEED);
+        frame_system::Account::<T>::mutate(&who, |_| {}); // Init AccountData
+        let attestation = Attestation {
+            who: who.clone(),
+            issuer,
+            tier: u8::MAX,
+            expires_at: frame_system::Pallet::<T>::block_number().saturating_add(10u32.into()),
+            nonce: 0,
+        };
+        let signature = T::BenchmarkHelper::sign(&Pallet::<T>::attestation_payload(&attestation));
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(who.clone()), attestation, signature);
+
+        assert_eq!(
+            frame_system::Account::<T>::get(&who).data.rate.status,
+            crate::Status::Tier(u8::MAX)
+        );
+        assert_eq!(ClaimNonces::<T>::get(&who), 1);
+    }
+
+    #[benchmark]
+    fn set_reputation_rules() {
+        let rules: BoundedVec<_, T::MaxReputationRules> = (0..T::MaxReputationRules::get())
+            .map(|i| ReputationRule {
+                min_age: i.into(),
+                min_clean_periods: i,
+                tier: i as u8,
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, rules.clone());
+
+        assert_eq!(ReputationRules::<T>::get(), rules);
+    }
+
+    /// Counts a transaction of a new account against its quota.
+    ///
+    /// The worst case checks a stamp that is not valid, before checking the quota, and makes the
+    /// account earn a tier from its reputation.
+    #[benchmark]
+    fn check_rate() -> Result<(), BenchmarkError> {
+        let who: T::AccountId = account("who", 0, SEED);
+        frame_system::Account::<T>::mutate(&who, |_| {}); // Init AccountData
+        StampDifficulty::<T>::put(u8::MAX);
+        BaseLimits::<T>::put(Limits {
+            max_tx: u32::MAX,
+            max_size: u32::MAX,
+        });
+        let rules: BoundedVec<_, T::MaxReputationRules> = (0..T::MaxReputationRules::get())
+            .map(|i| ReputationRule {
+                min_age: 0u32.into(),
+                min_clean_periods: 0,
+                tier: i as u8,
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        ReputationRules::<T>::put(rules);
+        let len = T::MaxSingleTxSize::get() as usize;
+        let call: <T as frame_system::Config>::RuntimeCall = frame_system::Call::<T>::remark {
+            remark: vec![0; len],
+        }
+        .into();
+        let info = DispatchInfo::default();
+
+        #[block]
+        {
+            CheckRate::<T>::with_stamp(0)
+                .test_run(
+                    RawOrigin::Signed(who.clone()).into(),
+                    &call,
+                    &info,
+                    len,
+                    0,
+                    |_| Ok(().into()),
+                )
+                .unwrap()
+                .unwrap();
+        }
+
+        assert!(BlockActiveAccounts::<T>::contains_key(&who));
+        assert_eq!(
+            frame_system::Account::<T>::get(&who)
+                .data
+                .rate
+                .tx_since_last,
+            1
+        );
+        Ok(())
     }
 
     impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
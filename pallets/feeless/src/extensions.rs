@@ -23,27 +23,41 @@
 //
 // You should have received a copy of the GPL along with this program.
 // If not, see <http://www.gnu.org/licenses/>.
-use crate::types::RateLimiter;
+use crate::{
+    types::{DerivativeCharge, EffectiveAccounts, IsExempt, RateLimiter},
+    WeightInfo,
+};
+use alloc::{vec, vec::Vec};
 use codec::{Decode, DecodeWithMemTracking, Encode};
 use core::marker::PhantomData;
-use frame_support::pallet_prelude::InvalidTransaction::ExhaustsResources;
+use frame_support::{pallet_prelude::InvalidTransaction, traits::Get};
 use scale_info::TypeInfo;
 use sp_runtime::{
-    impl_tx_ext_default,
-    traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, TransactionExtension},
+    traits::{
+        DispatchInfoOf, Dispatchable, PostDispatchInfoOf, TrailingZeroInput, TransactionExtension,
+    },
     transaction_validity::{TransactionSource, TransactionValidityError, ValidTransaction},
     DispatchResult, Weight,
 };
 
+/// The `InvalidTransaction::Custom` code of the transactions `CheckRate` rejects because an
+/// account they are charged to has no quota left.
+pub const QUOTA_EXHAUSTED: u8 = 1;
+
 /// A transaction extension for rate limiting.
+///
+/// It optionally carries the nonce of a proof-of-work stamp which, if valid, lets the
+/// transaction through beyond the quota of its signer. The stamp is checked against the payload
+/// that follows this extension, so `CheckRate` must come before `CheckNonce` for a stamp to be
+/// bound to a single transaction.
 #[derive(Encode, Decode, DecodeWithMemTracking, Clone, Eq, PartialEq, TypeInfo)]
 #[scale_info(skip_type_params(T))]
-pub struct CheckRate<T: frame_system::Config + Send + Sync>(PhantomData<T>);
+pub struct CheckRate<T: frame_system::Config + Send + Sync>(Option<u64>, PhantomData<T>);
 
 impl<T: frame_system::Config + Send + Sync> core::fmt::Debug for CheckRate<T> {
     #[cfg(feature = "std")]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "CheckRate")
+        write!(f, "CheckRate({:?})", self.0)
     }
 
     #[cfg(not(feature = "std"))]
@@ -53,13 +67,14 @@ impl<T: frame_system::Config + Send + Sync> core::fmt::Debug for CheckRate<T> {
 }
 
 pub struct Pre<T: frame_system::Config> {
-    who: Option<T::AccountId>,
+    charged: Vec<T::AccountId>,
+    stamped: bool,
 }
 
 impl<T: frame_system::Config> core::fmt::Debug for Pre<T> {
     #[cfg(feature = "std")]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "who: {:?}", self.who)
+        write!(f, "charged: {:?}, stamped: {}", self.charged, self.stamped)
     }
 
     #[cfg(not(feature = "std"))]
@@ -74,15 +89,83 @@ impl<T: frame_system::Config + Send + Sync> Default for CheckRate<T> {
     }
 }
 
+impl<T> CheckRate<T>
+where
+    T: crate::Config + Send + Sync,
+    T::AccountData: RateLimiter<T>,
+{
+    /// Returns the accounts charged for `call` signed by `who`, according to `DerivativeCharge`.
+    ///
+    /// `who` is charged in place of the accounts the call is dispatched as that do not exist,
+    /// so that counting their transactions does not create them.
+    fn charged(who: T::AccountId, call: &T::RuntimeCall) -> Vec<T::AccountId> {
+        let effective = T::EffectiveAccounts::effective_accounts(&who, call)
+            .into_iter()
+            .map(|account| {
+                if frame_system::Pallet::<T>::account_exists(&account) {
+                    account
+                } else {
+                    who.clone()
+                }
+            })
+            .collect();
+        Self::select(who, effective)
+    }
+
+    /// Selects the accounts charged among the signer `who` and the `effective` accounts,
+    /// according to `DerivativeCharge`.
+    fn select(who: T::AccountId, effective: Vec<T::AccountId>) -> Vec<T::AccountId> {
+        let mut charged = match T::DerivativeCharge::get() {
+            DerivativeCharge::Signer => vec![who],
+            DerivativeCharge::Effective if !effective.is_empty() => effective,
+            DerivativeCharge::Effective => vec![who],
+            DerivativeCharge::Both => [vec![who], effective].concat(),
+        };
+        charged.sort();
+        charged.dedup();
+        charged
+    }
+
+    /// Returns the most accounts charged for `call`, as selected for a placeholder signer when
+    /// all the accounts it is dispatched as exist: `EffectiveAccounts` is expected to return as
+    /// many accounts whoever signs the call.
+    fn charged_count(call: &T::RuntimeCall) -> u32 {
+        T::AccountId::decode(&mut TrailingZeroInput::zeroes()).map_or(1, |who| {
+            let effective = T::EffectiveAccounts::effective_accounts(&who, call);
+            Self::select(who, effective).len() as u32
+        })
+    }
+
+    /// Returns whether all the `charged` accounts have quota left for `len` bytes.
+    fn all_allowed(charged: &[T::AccountId], len: usize) -> bool {
+        let block = frame_system::Pallet::<T>::block_number();
+        charged.iter().all(|who| {
+            frame_system::Account::<T>::get(who)
+                .data
+                .is_allowed(block, len as u32)
+        })
+    }
+}
+
 impl<T: frame_system::Config + Send + Sync> CheckRate<T> {
     pub fn new() -> Self {
-        Self(PhantomData)
+        Self(None, PhantomData)
+    }
+
+    /// Creates the extension with the nonce of a proof-of-work stamp.
+    pub fn with_stamp(nonce: u64) -> Self {
+        Self(Some(nonce), PhantomData)
+    }
+
+    /// Returns the nonce of the proof-of-work stamp, if any.
+    pub fn stamp(&self) -> Option<u64> {
+        self.0
     }
 }
 
 impl<T> TransactionExtension<T::RuntimeCall> for CheckRate<T>
 where
-    T: frame_system::Config + Send + Sync,
+    T: crate::Config + Send + Sync,
     T::AccountData: RateLimiter<T>,
 {
     type Implicit = ();
@@ -91,17 +174,28 @@ where
 
     const IDENTIFIER: &'static str = "CheckRate";
 
-    impl_tx_ext_default!(T::RuntimeCall; weight);
+    /// Each charged account is counted as benchmarked by `check_rate`, stamp included, and
+    /// removed from `BlockActiveAccounts` in `on_finalize`, on top of checking whether the call
+    /// is exempt. Exempt calls are charged too, as exemptions depend on the signer.
+    fn weight(&self, call: &T::RuntimeCall) -> Weight {
+        T::WeightInfo::check_rate()
+            .saturating_add(T::DbWeight::get().writes(1))
+            .saturating_mul(Self::charged_count(call).into())
+            .saturating_add(T::ExemptCalls::weight(call))
+    }
 
     /// Validates a transaction based on rate limits.
+    ///
+    /// Calls in `ExemptCalls` are let through without being counted. Transactions with a valid
+    /// stamp are let through whatever the quota left, within `MaxSingleTxSize`, and counted.
     fn validate(
         &self,
         origin: <T::RuntimeCall as Dispatchable>::RuntimeOrigin,
-        _call: &T::RuntimeCall,
+        call: &T::RuntimeCall,
         _info: &DispatchInfoOf<T::RuntimeCall>,
         len: usize,
         _: (),
-        _implication: &impl Encode,
+        implication: &impl Encode,
         _source: TransactionSource,
     ) -> Result<
         (
@@ -111,38 +205,54 @@ where
         ),
         TransactionValidityError,
     > {
+        let unchecked = Pre {
+            charged: Vec::new(),
+            stamped: false,
+        };
         let Ok(who) = frame_system::ensure_signed(origin.clone()) else {
-            return Ok((Default::default(), Pre { who: None }, origin));
+            return Ok((Default::default(), unchecked, origin));
         };
+        if T::ExemptCalls::is_exempt(&who, call) {
+            return Ok((Default::default(), unchecked, origin));
+        }
 
-        let account_data = frame_system::Account::<T>::get(who.clone()).data;
-        let block = frame_system::Pallet::<T>::block_number();
-        if account_data.is_allowed(block, len as u32) {
-            Ok((
-                Default::default(),
-                Pre {
-                    who: Some(who.clone()),
-                },
-                origin,
-            ))
+        let stamped = self.0.is_some_and(|nonce| {
+            len as u32 <= T::MaxSingleTxSize::get()
+                && crate::Pallet::<T>::is_valid_stamp(&who, implication, nonce)
+        });
+        let charged = Self::charged(who, call);
+        if stamped || Self::all_allowed(&charged, len) {
+            Ok((Default::default(), Pre { charged, stamped }, origin))
         } else {
-            Err(TransactionValidityError::Invalid(ExhaustsResources))
+            Err(TransactionValidityError::Invalid(
+                InvalidTransaction::Custom(QUOTA_EXHAUSTED),
+            ))
         }
     }
 
-    /// Prepares data for post-dispatch processing.
+    /// Re-checks the rate limit against the in-block state and prepares data for post-dispatch
+    /// processing.
+    ///
+    /// Transactions of an account already applied in the block being built are accounted for
+    /// here, so a block can never hold more than the account's quota, whatever state the
+    /// transaction was validated against.
     fn prepare(
         self,
         val: Self::Val,
         _origin: &<T::RuntimeCall as Dispatchable>::RuntimeOrigin,
         _call: &T::RuntimeCall,
         _info: &DispatchInfoOf<T::RuntimeCall>,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::Pre, TransactionValidityError> {
+        if !val.stamped && !Self::all_allowed(&val.charged, len) {
+            return Err(TransactionValidityError::Invalid(
+                InvalidTransaction::Custom(QUOTA_EXHAUSTED),
+            ));
+        }
         Ok(val)
     }
 
-    /// Updates rate limits after transaction execution.
+    /// Updates reputation, rate limits and block usage after transaction execution.
     fn post_dispatch_details(
         pre: Self::Pre,
         _info: &DispatchInfoOf<T::RuntimeCall>,
@@ -150,11 +260,17 @@ where
         len: usize,
         _result: &DispatchResult,
     ) -> Result<Weight, TransactionValidityError> {
-        if let Some(who) = pre.who {
-            let mut account_data = frame_system::Account::<T>::get(who.clone()).data;
-            let block = frame_system::Pallet::<T>::block_number();
-            account_data.update_rate(block, len as u32);
-            frame_system::Account::<T>::mutate(who, |account| account.data = account_data);
+        let block = frame_system::Pallet::<T>::block_number();
+        for who in pre.charged {
+            // The dispatch may have removed the account, which must not be recreated.
+            let reached_quota = frame_system::Account::<T>::mutate_exists(&who, |account| {
+                account
+                    .as_mut()
+                    .map(|account| account.data.record(&who, block, len as u32))
+            });
+            if let Some(reached_quota) = reached_quota {
+                crate::Pallet::<T>::note_usage(&who, len as u32, reached_quota);
+            }
         }
         Ok(Weight::zero())
     }
@@ -25,13 +25,25 @@
 // If not, see <http://www.gnu.org/licenses/>.
 #![cfg_attr(not(feature = "std"), no_std)]
 #![doc = include_str!("../README.md")]
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::Encode;
 use frame_support::{
-    pallet_prelude::{EnsureOrigin, IsType},
-    traits::Get,
+    ensure,
+    pallet_prelude::{
+        Blake2_128Concat, BoundedVec, EnsureOrigin, IsType, OptionQuery, Parameter, StorageMap,
+        StorageValue, Twox64Concat, ValueQuery,
+    },
+    traits::{BuildGenesisConfig, Get, Hooks},
+    weights::Weight,
 };
 use frame_system::pallet_prelude::{BlockNumberFor, OriginFor};
 pub use pallet::*;
-use sp_runtime::{DispatchError, DispatchResult, SaturatedConversion};
+use sp_runtime::{
+    traits::{BlakeTwo256, CheckedSub, Hash, IdentifyAccount, Saturating, Verify, Zero},
+    DispatchError, DispatchResult, SaturatedConversion,
+};
 
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
@@ -50,6 +62,11 @@ pub use types::*;
 pub mod extensions;
 pub use extensions::*;
 
+/// Domain separator of the attestation payload.
+pub const ATTESTATION_CONTEXT: &[u8] = b"feeless/attestation";
+
+// The call enum expanded by `#[pallet::call]` trips these lints on the pinned toolchain.
+#[allow(unreachable_patterns, clippy::multiple_bound_locations)]
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
@@ -61,27 +78,244 @@ pub mod pallet {
     pub trait Config: frame_system::Config {
         /// The origin which may change account status. Root can always do this.
         type StatusOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Calls that bypass rate limiting entirely, so they stay available to throttled
+        /// accounts. They are neither checked nor counted, so only calls that are otherwise
+        /// restricted, e.g. to the signers allowed to dispatch them, should be exempted.
+        type ExemptCalls: IsExempt<Self::AccountId, <Self as frame_system::Config>::RuntimeCall>;
         /// The overarching runtime event type.
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
-        /// Maximum number of transactions allowed per account within the defined period.
+        /// Maximum number of transactions allowed per account within the defined period, unless
+        /// `BaseLimits` is set.
         type MaxTxByPeriod: Get<u32>;
-        /// Maximum size of transactions allowed per account within the defined period.
+        /// Maximum size of transactions allowed per account within the defined period, unless
+        /// `BaseLimits` is set.
         type MaxSizeByPeriod: Get<u32>;
+        /// Maximum size of a single transaction from a rate-limited account, whatever its
+        /// remaining budget.
+        type MaxSingleTxSize: Get<u32>;
         /// Duration (in blocks) defining the rate-limiting period.
         type Period: Get<u32>;
+        /// Maximum number of entries accepted by a single `set_status_batch` call.
+        type MaxStatusBatch: Get<u32>;
+        /// Maximum number of rules in `ReputationRules`.
+        type MaxReputationRules: Get<u32>;
+        /// Accounts charged by `CheckRate` for calls dispatched on behalf of other accounts.
+        type DerivativeCharge: Get<DerivativeCharge>;
+        /// Resolves the accounts calls are dispatched as, for `DerivativeCharge`.
+        type EffectiveAccounts: EffectiveAccounts<
+            Self::AccountId,
+            <Self as frame_system::Config>::RuntimeCall,
+        >;
+        /// Signature of the attestations accepted by `claim_status`.
+        type AttestationSignature: Verify<Signer = Self::AttestationSigner> + Parameter;
+        /// Public key of an attestation issuer, identifying its account.
+        type AttestationSigner: IdentifyAccount<AccountId = Self::AccountId>;
         /// A type representing the weights required by the dispatchables of this pallet.
         type WeightInfo: WeightInfo;
+        /// Provides signed attestations to the benchmarks.
+        #[cfg(feature = "runtime-benchmarks")]
+        type BenchmarkHelper: BenchmarkHelper<Self::AccountId, Self::AttestationSignature>;
+    }
+
+    #[pallet::type_value]
+    pub fn DefaultLimits<T: Config>() -> Limits {
+        Limits {
+            max_tx: T::MaxTxByPeriod::get(),
+            max_size: T::MaxSizeByPeriod::get(),
+        }
+    }
+
+    /// Limits applied to `Limited` accounts, and to tiers without a definition.
+    #[pallet::storage]
+    pub type BaseLimits<T: Config> = StorageValue<_, Limits, ValueQuery, DefaultLimits<T>>;
+
+    /// Limits applied to accounts with a `Tier` status.
+    #[pallet::storage]
+    pub type Tiers<T: Config> = StorageMap<_, Twox64Concat, u8, Limits, OptionQuery>;
+
+    /// Usage of the block being built, moved to `LastBlockUsage` in `on_finalize`.
+    #[pallet::storage]
+    pub type BlockUsage<T: Config> = StorageValue<_, UsageStats, ValueQuery>;
+
+    /// Usage of the last finalized block.
+    #[pallet::storage]
+    pub type LastBlockUsage<T: Config> = StorageValue<_, UsageStats, ValueQuery>;
+
+    /// Accounts that transacted in the block being built, cleared in `on_finalize`.
+    #[pallet::storage]
+    pub type BlockActiveAccounts<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+    /// Number of leading zero bits a proof-of-work stamp must have to let a transaction through
+    /// beyond its quota. Zero disables stamps.
+    #[pallet::storage]
+    pub type StampDifficulty<T: Config> = StorageValue<_, u8, ValueQuery>;
+
+    /// Accounts allowed to manage statuses on behalf of `StatusOrigin`.
+    #[pallet::storage]
+    pub type Registrars<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Registrar, OptionQuery>;
+
+    /// Statuses waiting for their account to be created.
+    ///
+    /// An entry is moved onto the account, and removed from this map, as soon as the account
+    /// starts to exist.
+    #[pallet::storage]
+    pub type PendingStatus<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Status, OptionQuery>;
+
+    /// Attestation issuers, with the highest tier each of them can attest.
+    #[pallet::storage]
+    pub type Issuers<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u8, OptionQuery>;
+
+    /// Number of statuses claimed by each account, which an attestation must match to be
+    /// claimed.
+    #[pallet::storage]
+    pub type ClaimNonces<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Usage history of the accounts that transacted.
+    #[pallet::storage]
+    pub type Reputations<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Reputation<BlockNumberFor<T>>, OptionQuery>;
+
+    /// Rules granting tiers from reputation. An account gets the highest tier among the rules
+    /// it satisfies.
+    #[pallet::storage]
+    pub type ReputationRules<T: Config> = StorageValue<
+        _,
+        BoundedVec<ReputationRule<BlockNumberFor<T>>, T::MaxReputationRules>,
+        ValueQuery,
+    >;
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+            // Accounts for the usage roll in `on_finalize`. Clearing `BlockActiveAccounts` is
+            // paid by the transactions that filled it, see `CheckRate::weight`, which also
+            // bounds the number of entries by the block weight.
+            T::DbWeight::get().reads_writes(1, 2)
+        }
+
+        fn on_finalize(_n: BlockNumberFor<T>) {
+            LastBlockUsage::<T>::put(BlockUsage::<T>::take());
+            let _ = BlockActiveAccounts::<T>::clear(u32::MAX, None);
+        }
+    }
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Initial account statuses. Accounts that do not exist yet get a pending status.
+        pub statuses: Vec<(T::AccountId, Status)>,
+        /// Initial base limits, the `Config` constants are used if not set.
+        pub limits: Option<Limits>,
+        /// Initial tier definitions.
+        pub tiers: Vec<(u8, Limits)>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T>
+    where
+        T: frame_system::Config<AccountData = AccountData<T::Balance, BlockNumberFor<T>>>
+            + Config
+            + pallet_balances::Config,
+    {
+        fn build(&self) {
+            if let Some(limits) = &self.limits {
+                BaseLimits::<T>::put(limits);
+            }
+            for (tier, limits) in &self.tiers {
+                Tiers::<T>::insert(tier, limits);
+            }
+            for (who, status) in &self.statuses {
+                Pallet::<T>::do_set_status(who, status.clone());
+            }
+        }
     }
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
-        StatusChanged { who: T::AccountId, status: Status },
+        StatusChanged {
+            who: T::AccountId,
+            status: Status,
+        },
+        /// The account does not exist yet; the status will be applied when it is created.
+        StatusPending {
+            who: T::AccountId,
+            status: Status,
+        },
+        /// A batch of statuses was processed.
+        StatusBatchSet {
+            updated: u32,
+            pending: u32,
+        },
+        RegistrarAdded {
+            registrar: T::AccountId,
+            allowance: u32,
+            max_status: Status,
+        },
+        RegistrarRemoved {
+            registrar: T::AccountId,
+        },
+        /// The account used up its quota for the current period.
+        QuotaReached {
+            who: T::AccountId,
+        },
+        /// A registrar changed the status of an account.
+        StatusSetByRegistrar {
+            registrar: T::AccountId,
+            who: T::AccountId,
+            status: Status,
+        },
+        /// The difficulty of proof-of-work stamps changed.
+        StampDifficultySet {
+            difficulty: u8,
+        },
+        IssuerAdded {
+            issuer: T::AccountId,
+            max_tier: u8,
+        },
+        IssuerRemoved {
+            issuer: T::AccountId,
+        },
+        /// An account claimed a status with an attestation.
+        StatusClaimed {
+            who: T::AccountId,
+            issuer: T::AccountId,
+            status: Status,
+        },
+        /// The reputation rules changed.
+        ReputationRulesSet {
+            rules: u32,
+        },
     }
 
     #[pallet::error]
     pub enum Error<T> {
-        StatusNotChanged,
+        /// The account is not a registrar.
+        NotRegistrar,
+        /// The current or requested status is above the registrar's `max_status`.
+        StatusAboveRegistrarMax,
+        /// The registrar has no promotions left.
+        AllowanceExhausted,
+        /// The attestation was issued to another account.
+        AttestationNotForCaller,
+        /// The attestation issuer is not in `Issuers`.
+        UnknownIssuer,
+        /// The attested tier is above the issuer's `max_tier`.
+        TierAboveIssuerMax,
+        /// The attestation can no longer be claimed.
+        AttestationExpired,
+        /// The attestation nonce does not match the claim nonce of the account.
+        StaleAttestation,
+        /// The attestation signature is not valid.
+        BadAttestationSignature,
+        /// The attested status is not above the current status of the account.
+        NotAnUpgrade,
+        /// The account has no quota left for the call.
+        QuotaExhausted,
     }
 
     #[pallet::call]
@@ -97,7 +331,9 @@ pub mod pallet {
         /// It is typically used for management tasks, such as managing account states
         /// during runtime upgrades or other administrative actions.
         ///
-        /// The status of the account will be updated to the provided `status` value.
+        /// The status of the account will be updated to the provided `status` value. If the
+        /// account does not exist yet, the status is kept in `PendingStatus` and applied once
+        /// the account is created, so an address can be whitelisted before it is funded.
         ///
         /// ## Arguments:
         /// - `origin`: The origin of the transaction (must be the root account).
@@ -112,28 +348,496 @@ pub mod pallet {
         ) -> DispatchResult {
             T::StatusOrigin::ensure_origin(origin)?;
 
+            Self::do_set_status(&who, status);
+            Ok(())
+        }
+
+        /// Sets the status of several accounts at once.
+        ///
+        /// Entries are applied in order exactly as with `set_status`: existing accounts are
+        /// updated and emit `StatusChanged`, others get a pending status and emit
+        /// `StatusPending`. A final `StatusBatchSet` event reports how many entries were
+        /// updated and left pending.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The origin of the transaction (must be the root account).
+        /// - `statuses`: The `(AccountId, Status)` pairs to apply, at most `MaxStatusBatch`.
+        #[pallet::call_index(1)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::set_status_batch(statuses.len() as u32))]
+        pub fn set_status_batch(
+            origin: OriginFor<T>,
+            statuses: BoundedVec<(T::AccountId, Status), T::MaxStatusBatch>,
+        ) -> DispatchResult {
+            T::StatusOrigin::ensure_origin(origin)?;
+
+            let (mut updated, mut pending) = (0u32, 0u32);
+            for (who, status) in statuses {
+                if Self::do_set_status(&who, status) {
+                    updated += 1;
+                } else {
+                    pending += 1;
+                }
+            }
+            Self::deposit_event(Event::StatusBatchSet { updated, pending });
+            Ok(())
+        }
+
+        /// Appoints a registrar, or replaces the allowance and maximum status of an existing one.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The origin of the transaction (must be the root account).
+        /// - `registrar`: The `AccountId` of the registrar.
+        /// - `allowance`: The number of promotions the registrar can grant.
+        /// - `max_status`: The highest `Status` the registrar can grant or revoke.
+        #[pallet::call_index(2)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::add_registrar())]
+        pub fn add_registrar(
+            origin: OriginFor<T>,
+            registrar: T::AccountId,
+            allowance: u32,
+            max_status: Status,
+        ) -> DispatchResult {
+            T::StatusOrigin::ensure_origin(origin)?;
+
+            Registrars::<T>::insert(
+                &registrar,
+                Registrar {
+                    allowance,
+                    max_status: max_status.clone(),
+                },
+            );
+            Self::deposit_event(Event::RegistrarAdded {
+                registrar,
+                allowance,
+                max_status,
+            });
+            Ok(())
+        }
+
+        /// Removes a registrar.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The origin of the transaction (must be the root account).
+        /// - `registrar`: The `AccountId` of the registrar to remove.
+        #[pallet::call_index(3)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::remove_registrar())]
+        pub fn remove_registrar(origin: OriginFor<T>, registrar: T::AccountId) -> DispatchResult {
+            T::StatusOrigin::ensure_origin(origin)?;
+
+            Registrars::<T>::take(&registrar).ok_or(Error::<T>::NotRegistrar)?;
+            Self::deposit_event(Event::RegistrarRemoved { registrar });
+            Ok(())
+        }
+
+        /// Sets the status of an account as a registrar.
+        ///
+        /// Both the current and the new status must be at most the registrar's `max_status`.
+        /// Raising the status of an account uses one promotion from the registrar's allowance,
+        /// lowering it is free. Accounts that do not exist yet get a pending status, as with
+        /// `set_status`.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The registrar signing the transaction.
+        /// - `who`: The `AccountId` of the account whose status is being set.
+        /// - `status`: The new `Status` to assign to the account.
+        #[pallet::call_index(4)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::registrar_set_status())]
+        pub fn registrar_set_status(
+            origin: OriginFor<T>,
+            who: T::AccountId,
+            status: Status,
+        ) -> DispatchResult {
+            let registrar = frame_system::ensure_signed(origin)?;
+
+            Registrars::<T>::try_mutate(&registrar, |info| {
+                let info = info.as_mut().ok_or(Error::<T>::NotRegistrar)?;
+                let current = Self::status(&who);
+                ensure!(
+                    current <= info.max_status && status <= info.max_status,
+                    Error::<T>::StatusAboveRegistrarMax
+                );
+                if status > current {
+                    info.allowance = info
+                        .allowance
+                        .checked_sub(1)
+                        .ok_or(Error::<T>::AllowanceExhausted)?;
+                }
+                Ok::<_, DispatchError>(())
+            })?;
+            Self::do_set_status(&who, status.clone());
+            Self::deposit_event(Event::StatusSetByRegistrar {
+                registrar,
+                who,
+                status,
+            });
+            Ok(())
+        }
+
+        /// Sets the difficulty of the proof-of-work stamps that let a transaction through beyond
+        /// its quota.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The origin of the transaction (must be the root account).
+        /// - `difficulty`: The number of leading zero bits required, or zero to disable stamps.
+        #[pallet::call_index(5)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::set_stamp_difficulty())]
+        pub fn set_stamp_difficulty(origin: OriginFor<T>, difficulty: u8) -> DispatchResult {
+            T::StatusOrigin::ensure_origin(origin)?;
+
+            StampDifficulty::<T>::put(difficulty);
+            Self::deposit_event(Event::StampDifficultySet { difficulty });
+            Ok(())
+        }
+
+        /// Appoints an attestation issuer, or replaces the maximum tier of an existing one.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The origin of the transaction (must be the root account).
+        /// - `issuer`: The `AccountId` of the issuer, whose key signs attestations.
+        /// - `max_tier`: The highest tier the issuer can attest.
+        #[pallet::call_index(6)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::add_issuer())]
+        pub fn add_issuer(
+            origin: OriginFor<T>,
+            issuer: T::AccountId,
+            max_tier: u8,
+        ) -> DispatchResult {
+            T::StatusOrigin::ensure_origin(origin)?;
+
+            Issuers::<T>::insert(&issuer, max_tier);
+            Self::deposit_event(Event::IssuerAdded { issuer, max_tier });
+            Ok(())
+        }
+
+        /// Removes an attestation issuer. Attestations it signed can no longer be claimed.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The origin of the transaction (must be the root account).
+        /// - `issuer`: The `AccountId` of the issuer.
+        #[pallet::call_index(7)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::remove_issuer())]
+        pub fn remove_issuer(origin: OriginFor<T>, issuer: T::AccountId) -> DispatchResult {
+            T::StatusOrigin::ensure_origin(origin)?;
+
+            Issuers::<T>::take(&issuer).ok_or(Error::<T>::UnknownIssuer)?;
+            Self::deposit_event(Event::IssuerRemoved { issuer });
+            Ok(())
+        }
+
+        /// Upgrades the status of the caller to the tier of an attestation.
+        ///
+        /// The attestation must be issued to the caller, signed by an issuer allowed to attest
+        /// its tier, not expired, and carry the current claim nonce of the caller, which is then
+        /// incremented so that it cannot be replayed.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The account the attestation was issued to.
+        /// - `attestation`: The attestation.
+        /// - `signature`: The signature of `attestation_payload(attestation)` by the issuer.
+        #[pallet::call_index(8)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::claim_status())]
+        pub fn claim_status(
+            origin: OriginFor<T>,
+            attestation: Attestation<T::AccountId, BlockNumberFor<T>>,
+            signature: T::AttestationSignature,
+        ) -> DispatchResult {
+            let who = frame_system::ensure_signed(origin)?;
+            ensure!(attestation.who == who, Error::<T>::AttestationNotForCaller);
+            let max_tier =
+                Issuers::<T>::get(&attestation.issuer).ok_or(Error::<T>::UnknownIssuer)?;
+            ensure!(attestation.tier <= max_tier, Error::<T>::TierAboveIssuerMax);
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= attestation.expires_at,
+                Error::<T>::AttestationExpired
+            );
+            ensure!(
+                ClaimNonces::<T>::get(&who) == attestation.nonce,
+                Error::<T>::StaleAttestation
+            );
+            ensure!(
+                signature.verify(
+                    &Self::attestation_payload(&attestation)[..],
+                    &attestation.issuer
+                ),
+                Error::<T>::BadAttestationSignature
+            );
+            let status = Status::Tier(attestation.tier);
+            ensure!(status > Self::status(&who), Error::<T>::NotAnUpgrade);
+
+            ClaimNonces::<T>::insert(&who, attestation.nonce.saturating_add(1));
+            Self::do_set_status(&who, status.clone());
+            Self::deposit_event(Event::StatusClaimed {
+                who,
+                issuer: attestation.issuer,
+                status,
+            });
+            Ok(())
+        }
+
+        /// Replaces the rules granting tiers from reputation.
+        ///
+        /// They apply to accounts whose status is `Limited`, or the tier reputation granted
+        /// them, the next time such an account starts a period.
+        ///
+        /// ## Arguments:
+        /// - `origin`: The origin of the transaction (must be the root account).
+        /// - `rules`: The new rules.
+        #[pallet::call_index(9)]
+        #[pallet::weight(<T as pallet::Config>::WeightInfo::set_reputation_rules())]
+        pub fn set_reputation_rules(
+            origin: OriginFor<T>,
+            rules: BoundedVec<ReputationRule<BlockNumberFor<T>>, T::MaxReputationRules>,
+        ) -> DispatchResult {
+            T::StatusOrigin::ensure_origin(origin)?;
+
+            let count = rules.len() as u32;
+            ReputationRules::<T>::put(rules);
+            Self::deposit_event(Event::ReputationRulesSet { rules: count });
+            Ok(())
+        }
+    }
+}
+
+impl<T> Pallet<T>
+where
+    T: frame_system::Config<AccountData = AccountData<T::Balance, BlockNumberFor<T>>>
+        + Config
+        + pallet_balances::Config,
+{
+    /// Returns what is left of the quota of `who` for the next block.
+    ///
+    /// The next block is used because this is the one transactions are validated against.
+    pub fn quota(who: &T::AccountId) -> Quota<BlockNumberFor<T>> {
+        let rate = frame_system::Account::<T>::get(who).data.rate;
+        let block = frame_system::Pallet::<T>::block_number().saturating_add(1u32.into());
+        let (tx, size, start) = if Self::in_period(&rate, block) {
+            (rate.tx_since_last, rate.size_since_last, rate.last_block)
+        } else {
+            (0, 0, block)
+        };
+        let limits = Self::limits(&rate.status);
+        Quota {
+            remaining_tx: limits.as_ref().map(|l| l.max_tx.saturating_sub(tx)),
+            remaining_size: limits.map(|l| l.max_size.saturating_sub(size)),
+            resets_at: start.saturating_add(T::Period::get().into()),
+            status: rate.status,
+        }
+    }
+
+    /// Returns the status of an account, or its pending status if it does not exist yet.
+    fn status(who: &T::AccountId) -> Status {
+        frame_system::Account::<T>::try_get(who)
+            .map(|account| account.data.rate.status)
+            .unwrap_or_else(|_| PendingStatus::<T>::get(who).unwrap_or_default())
+    }
+
+    /// Updates the status of an account and returns whether it already existed.
+    ///
+    /// Existing accounts are updated in place and emit `StatusChanged`. Otherwise the status is
+    /// stored in `PendingStatus` (or cleared, for the default `Limited`) and `StatusPending` is
+    /// emitted; no account is created.
+    fn do_set_status(who: &T::AccountId, status: Status) -> bool {
+        let exists = frame_system::Account::<T>::mutate_exists(who, |account| {
+            account
+                .as_mut()
+                .map(|account| account.data.rate.status = status.clone())
+                .is_some()
+        });
+        if exists {
             Self::deposit_event(Event::StatusChanged {
                 who: who.clone(),
-                status: status.clone(),
+                status,
             });
-            frame_system::Account::<T>::try_mutate_exists(who.clone(), |account| {
-                if let Some(ref mut account) = account {
-                    account.data.rate.status = status.clone();
-                    Ok(())
-                } else {
-                    Err(Error::<T>::StatusNotChanged.into())
+        } else {
+            if status == Status::default() {
+                PendingStatus::<T>::remove(who);
+            } else {
+                PendingStatus::<T>::insert(who, status.clone());
+            }
+            Self::deposit_event(Event::StatusPending {
+                who: who.clone(),
+                status,
+            });
+        }
+        exists
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Records a transaction counted by `CheckRate` in the usage of the current block.
+    ///
+    /// `reached_quota` is set when this transaction used up the account's quota, in which case
+    /// `QuotaReached` is emitted.
+    pub(crate) fn note_usage(who: &T::AccountId, len: u32, reached_quota: bool) {
+        let first_in_block = !BlockActiveAccounts::<T>::contains_key(who);
+        if first_in_block {
+            BlockActiveAccounts::<T>::insert(who, ());
+        }
+        BlockUsage::<T>::mutate(|usage| {
+            if first_in_block {
+                usage.active_accounts = usage.active_accounts.saturating_add(1);
+            }
+            usage.transactions = usage.transactions.saturating_add(1);
+            usage.bytes = usage.bytes.saturating_add(len);
+            if reached_quota {
+                usage.accounts_at_quota = usage.accounts_at_quota.saturating_add(1);
+            }
+        });
+        if reached_quota {
+            Reputations::<T>::mutate(who, |reputation| {
+                if let Some(reputation) = reputation {
+                    reputation.period_exhausted = true;
                 }
-            })
+            });
+            Self::deposit_event(Event::QuotaReached { who: who.clone() });
         }
     }
+
+    /// Updates the reputation of `who` before a transaction at block `b` is counted, and applies
+    /// the tier it earned to `rate`.
+    ///
+    /// This only touches storage for the first transaction of an account and the first one of
+    /// each period, which closes the previous period: it is clean if the quota was not used up.
+    /// The status is only changed if it is `Limited` or the tier previously granted by
+    /// reputation, so statuses set by other means are left alone.
+    pub(crate) fn update_reputation(
+        who: &T::AccountId,
+        rate: &mut Rate<BlockNumberFor<T>>,
+        b: BlockNumberFor<T>,
+    ) {
+        if rate.tx_since_last > 0 && Self::in_period(rate, b) {
+            return;
+        }
+        Reputations::<T>::mutate(who, |reputation| {
+            let reputation = reputation.get_or_insert(Reputation {
+                first_seen: b,
+                clean_periods: 0,
+                period_exhausted: false,
+                tier: None,
+            });
+            if rate.tx_since_last > 0 {
+                reputation.clean_periods = if reputation.period_exhausted {
+                    0
+                } else {
+                    reputation.clean_periods.saturating_add(1)
+                };
+            }
+            reputation.period_exhausted = false;
+
+            let managed = match rate.status {
+                Status::Limited => reputation.tier.is_none(),
+                Status::Tier(tier) => reputation.tier == Some(tier),
+                Status::Unlimited => false,
+            };
+            let age = b.saturating_sub(reputation.first_seen);
+            let earned = ReputationRules::<T>::get()
+                .iter()
+                .filter(|rule| {
+                    age >= rule.min_age && reputation.clean_periods >= rule.min_clean_periods
+                })
+                .map(|rule| rule.tier)
+                .max();
+            if managed && earned != reputation.tier {
+                reputation.tier = earned;
+                rate.status = earned.map_or(Status::Limited, Status::Tier);
+                Self::deposit_event(Event::StatusChanged {
+                    who: who.clone(),
+                    status: rate.status.clone(),
+                });
+            }
+        });
+    }
+
+    /// Checks a proof-of-work stamp against the payload of a transaction signed by `who`.
+    ///
+    /// The stamp is valid if `blake2_256((who, blake2_256(payload), nonce))` starts with at least
+    /// `StampDifficulty` zero bits. Stamps are never valid while the difficulty is zero.
+    pub fn is_valid_stamp(who: &T::AccountId, payload: &impl Encode, nonce: u64) -> bool {
+        let difficulty = StampDifficulty::<T>::get() as u32;
+        if difficulty == 0 {
+            return false;
+        }
+        let work = BlakeTwo256::hash_of(&(who, BlakeTwo256::hash_of(payload), nonce));
+        let mut zeros = 0;
+        for byte in work.as_bytes() {
+            zeros += byte.leading_zeros();
+            if *byte != 0 {
+                break;
+            }
+        }
+        zeros >= difficulty
+    }
+
+    /// Returns the message an issuer signs to attest `attestation`.
+    ///
+    /// It is bound to this chain by its genesis hash.
+    pub fn attestation_payload(
+        attestation: &Attestation<T::AccountId, BlockNumberFor<T>>,
+    ) -> Vec<u8> {
+        let genesis = frame_system::Pallet::<T>::block_hash(BlockNumberFor::<T>::zero());
+        (ATTESTATION_CONTEXT, genesis, attestation).encode()
+    }
+
+    /// Returns whether block `b` falls within the current period of `rate`.
+    ///
+    /// A `last_block` ahead of `b`, which can happen after a revert or a migration, is treated
+    /// as an expired period so that the account is not locked out until the chain catches up.
+    fn in_period(rate: &Rate<BlockNumberFor<T>>, b: BlockNumberFor<T>) -> bool {
+        b.checked_sub(&rate.last_block)
+            .is_some_and(|elapsed| elapsed.saturated_into::<u32>() < T::Period::get())
+    }
+
+    /// Returns the limits applying to `status`, or `None` if it is not rate limited.
+    pub fn limits(status: &Status) -> Option<Limits> {
+        match status {
+            Status::Unlimited => None,
+            Status::Limited => Some(BaseLimits::<T>::get()),
+            Status::Tier(tier) => Some(Tiers::<T>::get(tier).unwrap_or_else(BaseLimits::<T>::get)),
+        }
+    }
+}
+
+impl<T> ChargeQuota<T::AccountId> for Pallet<T>
+where
+    T: frame_system::Config<AccountData = AccountData<T::Balance, BlockNumberFor<T>>>
+        + Config
+        + pallet_balances::Config,
+{
+    /// Charges `who` like `CheckRate` would, for pallets that dispatch calls on behalf of
+    /// accounts only known at dispatch. An account that does not exist is not charged, so that
+    /// it is not created.
+    fn charge(who: &T::AccountId, len: u32) -> DispatchResult {
+        let block = frame_system::Pallet::<T>::block_number();
+        let reached_quota = frame_system::Account::<T>::try_mutate_exists(who, |account| {
+            let Some(account) = account else {
+                return Ok(None);
+            };
+            let data = &mut account.data;
+            ensure!(
+                RateLimiter::<T>::is_allowed(data, block, len),
+                Error::<T>::QuotaExhausted
+            );
+            Ok::<_, DispatchError>(Some(RateLimiter::<T>::record(data, who, block, len)))
+        })?;
+        if let Some(reached_quota) = reached_quota {
+            Self::note_usage(who, len, reached_quota);
+        }
+        Ok(())
+    }
 }
 
 /// Implements the storage backend for custom account data (same as the default from pallet
 /// balances.
+///
+/// This is also where a `PendingStatus` is moved onto an account when it is first funded. The
+/// `OnNewAccount` hook of `frame_system` cannot be used for this: it runs inside the
+/// `frame_system::Account` mutation that creates the account, which overwrites any change the
+/// hook makes to the same entry.
 impl<T> frame_support::traits::StoredMap<T::AccountId, pallet_balances::AccountData<T::Balance>>
     for Pallet<T>
 where
     T: frame_system::Config<AccountData = AccountData<T::Balance, BlockNumberFor<T>>>
+        + Config
         + pallet_balances::Config,
 {
     fn get(k: &T::AccountId) -> pallet_balances::AccountData<T::Balance> {
@@ -156,9 +860,23 @@ where
         if frame_system::Pallet::<T>::providers(k) > 0
             || frame_system::Pallet::<T>::sufficients(k) > 0
         {
+            let pending = if is_default {
+                PendingStatus::<T>::take(k)
+            } else {
+                None
+            };
             frame_system::Account::<T>::mutate(k, |a| {
-                a.data.balance = some_data.unwrap_or_default()
+                a.data.balance = some_data.unwrap_or_default();
+                if let Some(status) = pending.clone() {
+                    a.data.rate.status = status;
+                }
             });
+            if let Some(status) = pending {
+                Self::deposit_event(Event::StatusChanged {
+                    who: k.clone(),
+                    status,
+                });
+            }
         } else {
             frame_system::Account::<T>::remove(k)
         }
@@ -186,17 +904,30 @@ where
     /// * `b` - The current block number.
     /// * `len` - The size of the transaction in bytes.
     ///
+    /// Limits are inclusive: a transaction is allowed if, once included, the
+    /// period holds at most `max_tx` transactions and `max_size` bytes, and
+    /// the transaction itself is at most `MaxSingleTxSize` bytes.
+    ///
     /// # Returns
     /// `true` if the transaction is allowed, `false` otherwise.
     fn is_allowed(&self, b: BlockNumberFor<T>, len: u32) -> bool {
-        if self.rate.status == Status::Unlimited {
-            true
-        } else if (b - self.rate.last_block).saturated_into::<u32>() < T::Period::get() {
-            self.rate.tx_since_last < T::MaxTxByPeriod::get()
-                && self.rate.size_since_last.saturating_add(len) < T::MaxSizeByPeriod::get()
-        } else {
-            len < T::MaxSizeByPeriod::get()
+        let Some(limits) = Pallet::<T>::limits(&self.rate.status) else {
+            return true;
+        };
+        if len > T::MaxSingleTxSize::get() {
+            return false;
         }
+        let (tx, size) = if Pallet::<T>::in_period(&self.rate, b) {
+            (self.rate.tx_since_last, self.rate.size_since_last)
+        } else {
+            (0, 0)
+        };
+        tx < limits.max_tx && size.saturating_add(len) <= limits.max_size
+    }
+
+    /// Updates the reputation of the account, see `Pallet::update_reputation`.
+    fn update_reputation(&mut self, who: &T::AccountId, b: BlockNumberFor<T>) {
+        Pallet::<T>::update_reputation(who, &mut self.rate, b);
     }
 
     /// Updates the rate limiter's internal statistics, such as the number of
@@ -209,11 +940,12 @@ where
     ///
     /// This method will reset the transaction count and size if the current
     /// block number exceeds the specified period. Otherwise, it will update
-    /// the transaction count and size based on the new transaction.
+    /// the transaction count and size based on the new transaction. Counters
+    /// saturate instead of overflowing.
     fn update_rate(&mut self, b: BlockNumberFor<T>, len: u32) {
-        if (b - self.rate.last_block).saturated_into::<u32>() < T::Period::get() {
-            self.rate.tx_since_last += 1;
-            self.rate.size_since_last += len;
+        if Pallet::<T>::in_period(&self.rate, b) {
+            self.rate.tx_since_last = self.rate.tx_since_last.saturating_add(1);
+            self.rate.size_since_last = self.rate.size_since_last.saturating_add(len);
         } else {
             self.rate.tx_since_last = 1;
             self.rate.size_since_last = len;
@@ -26,11 +26,26 @@
 use crate as pallet;
 use frame_support::derive_impl;
 use frame_system::EnsureRoot;
-use sp_runtime::{traits::parameter_types, BuildStorage};
+use sp_runtime::{
+    generic,
+    testing::{TestSignature, UintAuthorityId},
+    traits::{parameter_types, BlakeTwo256},
+    BuildStorage,
+};
 
 type Balance = u64;
 type BlockNumber = u64;
-type Block = frame_system::mocking::MockBlock<Test>;
+pub type TxExtension = pallet::CheckRate<Test>;
+pub type UncheckedExtrinsic =
+    frame_system::mocking::MockUncheckedExtrinsic<Test, UintAuthorityId, TxExtension>;
+pub type Block = generic::Block<generic::Header<BlockNumber, BlakeTwo256>, UncheckedExtrinsic>;
+pub type Executive = frame_executive::Executive<
+    Test,
+    Block,
+    frame_system::ChainContext<Test>,
+    Test,
+    AllPalletsWithSystem,
+>;
 
 #[frame_support::runtime]
 mod runtime {
@@ -58,6 +73,9 @@ mod runtime {
 
     #[runtime::pallet_index(2)]
     pub type Balances = pallet_balances::Pallet<Test>;
+
+    #[runtime::pallet_index(3)]
+    pub type Utility = pallet_utility::Pallet<Test>;
 }
 
 #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
@@ -72,14 +90,99 @@ impl pallet_balances::Config for Test {
     type Balance = Balance;
 }
 
+impl pallet_utility::Config for Test {
+    type PalletsOrigin = OriginCaller;
+    type RuntimeCall = RuntimeCall;
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = ();
+}
+
+/// Account whose `remark` calls are exempt.
+pub const PRIVILEGED: u64 = 42;
+
+/// Exempts `remark_with_event`, `remark` signed by `PRIVILEGED`, and non-empty batches made only
+/// of exempt calls.
+pub struct ExemptCalls;
+impl pallet::IsExempt<u64, RuntimeCall> for ExemptCalls {
+    fn is_exempt(who: &u64, call: &RuntimeCall) -> bool {
+        match call {
+            RuntimeCall::System(frame_system::Call::remark_with_event { .. }) => true,
+            RuntimeCall::System(frame_system::Call::remark { .. }) => *who == PRIVILEGED,
+            RuntimeCall::Utility(
+                pallet_utility::Call::batch { calls }
+                | pallet_utility::Call::batch_all { calls }
+                | pallet_utility::Call::force_batch { calls },
+            ) => !calls.is_empty() && calls.iter().all(|call| Self::is_exempt(who, call)),
+            _ => false,
+        }
+    }
+}
+
+/// Resolves `as_derivative` accounts, including inside batches.
+pub struct DerivativeAccounts;
+impl pallet::EffectiveAccounts<u64, RuntimeCall> for DerivativeAccounts {
+    fn effective_accounts(who: &u64, call: &RuntimeCall) -> Vec<u64> {
+        match call {
+            RuntimeCall::Utility(pallet_utility::Call::as_derivative { index, call }) => {
+                let derivative = Utility::derivative_account_id(*who, *index);
+                let nested = Self::effective_accounts(&derivative, call);
+                if nested.is_empty() {
+                    vec![derivative]
+                } else {
+                    nested
+                }
+            }
+            RuntimeCall::Utility(
+                pallet_utility::Call::batch { calls }
+                | pallet_utility::Call::batch_all { calls }
+                | pallet_utility::Call::force_batch { calls },
+            ) => calls
+                .iter()
+                .flat_map(|call| Self::effective_accounts(who, call))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+frame_support::parameter_types! {
+    pub static DerivativeChargeMode: pallet::DerivativeCharge = pallet::DerivativeCharge::Both;
+}
+
 parameter_types! {
     pub const MaxTxByPeriod: u32 = 5;
     pub const MaxSizeByPeriod: u32 = 40;
+    pub const MaxSingleTxSize: u32 = 64;
     pub const Period: u32 = 10;
+    pub const MaxStatusBatch: u32 = 4;
+    pub const MaxReputationRules: u32 = 4;
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+pub struct BenchmarkHelper;
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet::BenchmarkHelper<u64, TestSignature> for BenchmarkHelper {
+    fn issuer() -> u64 {
+        1_000
+    }
+
+    fn sign(message: &[u8]) -> TestSignature {
+        TestSignature(Self::issuer(), message.to_vec())
+    }
 }
 
 impl pallet::Config for Test {
+    type AttestationSignature = TestSignature;
+    type AttestationSigner = UintAuthorityId;
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = BenchmarkHelper;
+    type DerivativeCharge = DerivativeChargeMode;
+    type EffectiveAccounts = DerivativeAccounts;
+    type ExemptCalls = ExemptCalls;
+    type MaxReputationRules = MaxReputationRules;
+    type MaxSingleTxSize = MaxSingleTxSize;
     type MaxSizeByPeriod = MaxSizeByPeriod;
+    type MaxStatusBatch = MaxStatusBatch;
     type MaxTxByPeriod = MaxTxByPeriod;
     type Period = Period;
     type RuntimeEvent = RuntimeEvent;
@@ -87,12 +190,22 @@ impl pallet::Config for Test {
     type WeightInfo = ();
 }
 
+/// Accounts that exist at genesis. Transactions are only counted against the quota of existing
+/// accounts.
+pub const ENDOWED: [u64; 4] = [1, 2, 3, PRIVILEGED];
+
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    frame_system::GenesisConfig::<Test>::default()
-        .build_storage()
-        .unwrap()
-        .into()
+    RuntimeGenesisConfig {
+        balances: pallet_balances::GenesisConfig {
+            balances: ENDOWED.iter().map(|who| (*who, 100_000)).collect(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build_storage()
+    .unwrap()
+    .into()
 }
 
 /// A simple call, which one doesn't matter.
@@ -23,13 +23,24 @@
 //
 // You should have received a copy of the GPL along with this program.
 // If not, see <http://www.gnu.org/licenses/>.
-use crate::{mock::*, CheckRate};
+use crate::{mock::*, CheckRate, RateLimiter};
+use codec::{Decode, Encode};
 use frame_support::{
-    assert_err, assert_noop, assert_ok, dispatch::DispatchInfo, pallet_prelude::InvalidTransaction,
-    traits::fungible::Mutate,
+    assert_err, assert_noop, assert_ok,
+    dispatch::DispatchInfo,
+    pallet_prelude::InvalidTransaction,
+    traits::{fungible::Mutate, Hooks},
+    BoundedVec,
 };
 use frame_system::RawOrigin;
-use sp_runtime::{traits::DispatchTransaction, transaction_validity::TransactionValidityError};
+use proptest::prelude::*;
+use sp_runtime::{
+    generic::Header,
+    testing::{TestSignature, UintAuthorityId},
+    traits::{DispatchTransaction, Header as _, TransactionExtension, TxBaseImplication},
+    transaction_validity::{TransactionSource, TransactionValidityError},
+    BuildStorage,
+};
 
 #[test]
 fn transaction_work() {
@@ -63,7 +74,7 @@ fn transaction_fail_after_quota() {
             CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, len, 0, |_| Ok(
                 Default::default()
             )),
-            TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources,)
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED),)
         );
     })
 }
@@ -87,7 +98,7 @@ fn transaction_success_after_period() {
             CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, len, 0, |_| Ok(
                 Default::default()
             )),
-            TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources,)
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED),)
         );
         System::set_block_number(<Test as crate::Config>::Period::get().into());
         assert_ok!(
@@ -103,12 +114,12 @@ fn too_big_fail() {
     new_test_ext().execute_with(|| {
         System::set_block_number(<Test as crate::Config>::Period::get().into());
         let info = DispatchInfo::default();
-        let len = <Test as crate::Config>::MaxSizeByPeriod::get() as usize;
+        let len = <Test as crate::Config>::MaxSizeByPeriod::get() as usize + 1;
         assert_err!(
             CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, len, 0, |_| Ok(
                 Default::default()
             )),
-            TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources,)
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED),)
         );
     });
 }
@@ -118,7 +129,7 @@ fn small_success_until_limit() {
     new_test_ext().execute_with(|| {
         let info = DispatchInfo::default();
         let len = (<Test as crate::Config>::MaxSizeByPeriod::get() / 4) as usize;
-        for _ in 0..3 {
+        for _ in 0..4 {
             assert_ok!(CheckRate::<Test>::new().test_run(
                 Some(1).into(),
                 CALL,
@@ -132,7 +143,7 @@ fn small_success_until_limit() {
             CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, len, 0, |_| Ok(
                 Default::default()
             )),
-            TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources,)
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED),)
         );
         System::set_block_number(<Test as crate::Config>::Period::get().into());
         assert_ok!(
@@ -143,6 +154,96 @@ fn small_success_until_limit() {
     })
 }
 
+#[test]
+fn limits_are_inclusive() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo::default();
+        let run = |len| {
+            CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, len, 0, |_| {
+                Ok(Default::default())
+            })
+        };
+        // Both the transaction count and the byte budget can be used up exactly.
+        for _ in 1..<Test as crate::Config>::MaxTxByPeriod::get() {
+            assert_ok!(run(0));
+        }
+        assert_ok!(run(<Test as crate::Config>::MaxSizeByPeriod::get() as usize));
+        assert_eq!(
+            frame_system::Account::<Test>::get(1).data.rate,
+            crate::Rate {
+                last_block: 0,
+                tx_since_last: <Test as crate::Config>::MaxTxByPeriod::get(),
+                size_since_last: <Test as crate::Config>::MaxSizeByPeriod::get(),
+                status: crate::Status::Limited,
+            }
+        );
+        assert_err!(
+            run(0),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+    });
+}
+
+#[test]
+fn byte_budget_boundary() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo::default();
+        let run = |len| {
+            CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, len, 0, |_| {
+                Ok(Default::default())
+            })
+        };
+        let max = <Test as crate::Config>::MaxSizeByPeriod::get() as usize;
+        assert_ok!(run(max - 1));
+        assert_ok!(run(1));
+        assert_err!(
+            run(1),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+    });
+}
+
+#[test]
+fn single_tx_size_limit() {
+    new_test_ext().execute_with(|| {
+        crate::Tiers::<Test>::insert(
+            1,
+            crate::Limits {
+                max_tx: 10,
+                max_size: 1_000,
+            },
+        );
+        Balances::set_balance(&1, 100_000); // Init AccountData
+        Balances::set_balance(&2, 100_000); // Init AccountData
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            1,
+            crate::Status::Tier(1)
+        ));
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            2,
+            crate::Status::Unlimited
+        ));
+
+        let info = DispatchInfo::default();
+        let run = |who, len| {
+            CheckRate::<Test>::new().test_run(Some(who).into(), CALL, &info, len, 0, |_| {
+                Ok(Default::default())
+            })
+        };
+        let max = <Test as crate::Config>::MaxSingleTxSize::get() as usize;
+        // The tier budget is large enough, but a single transaction is capped.
+        assert_err!(
+            run(1, max + 1),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        assert_ok!(run(1, max));
+        // Unlimited accounts are not capped.
+        assert_ok!(run(2, max + 1));
+    });
+}
+
 #[test]
 fn too_big_but_unsigned() {
     new_test_ext().execute_with(|| {
@@ -197,10 +298,6 @@ fn set_to_unlimited() {
             Feeless::set_status(RuntimeOrigin::signed(1), 1, crate::Status::Unlimited),
             frame_support::error::BadOrigin
         );
-        assert_err!(
-            Feeless::set_status(RawOrigin::Root.into(), 10_000, crate::Status::Unlimited),
-            crate::Error::<Test>::StatusNotChanged
-        );
         assert_eq!(
             frame_system::Account::<Test>::get(1).data.rate.status,
             crate::Status::Limited
@@ -223,7 +320,7 @@ fn set_to_unlimited() {
         );
 
         let info = DispatchInfo::default();
-        let len = <Test as crate::Config>::MaxSizeByPeriod::get() as usize;
+        let len = <Test as crate::Config>::MaxSizeByPeriod::get() as usize + 1;
         assert_ok!(CheckRate::<Test>::new().test_run(
             RuntimeOrigin::signed(1),
             CALL,
@@ -259,7 +356,1377 @@ fn set_to_unlimited() {
                 0,
                 |_| Ok(Default::default())
             ),
-            TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources)
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+    });
+}
+
+#[test]
+fn set_status_before_account_exists() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            10_000,
+            crate::Status::Unlimited
+        ));
+        // No dust account is created, the status waits for the account.
+        assert!(!frame_system::Account::<Test>::contains_key(10_000));
+        assert_eq!(
+            crate::PendingStatus::<Test>::get(10_000),
+            Some(crate::Status::Unlimited)
+        );
+        System::assert_last_event(
+            crate::Event::StatusPending {
+                who: 10_000,
+                status: crate::Status::Unlimited,
+            }
+            .into(),
+        );
+
+        Balances::set_balance(&10_000, 100_000);
+        assert_eq!(
+            frame_system::Account::<Test>::get(10_000).data.rate.status,
+            crate::Status::Unlimited
+        );
+        assert_eq!(crate::PendingStatus::<Test>::get(10_000), None);
+        System::assert_has_event(
+            crate::Event::StatusChanged {
+                who: 10_000,
+                status: crate::Status::Unlimited,
+            }
+            .into(),
+        );
+
+        // Once reaped, the account starts over with the default status.
+        Balances::set_balance(&10_000, 0);
+        assert!(!frame_system::Account::<Test>::contains_key(10_000));
+        Balances::set_balance(&10_000, 100_000);
+        assert_eq!(
+            frame_system::Account::<Test>::get(10_000).data.rate.status,
+            crate::Status::Limited
+        );
+    });
+}
+
+#[test]
+fn clear_pending_status() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            10_000,
+            crate::Status::Unlimited
+        ));
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            10_000,
+            crate::Status::Limited
+        ));
+        assert_eq!(crate::PendingStatus::<Test>::get(10_000), None);
+
+        Balances::set_balance(&10_000, 100_000);
+        assert_eq!(
+            frame_system::Account::<Test>::get(10_000).data.rate.status,
+            crate::Status::Limited
+        );
+    });
+}
+
+#[test]
+fn set_status_batch_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Balances::set_balance(&1, 100_000); // Init AccountData
+        Balances::set_balance(&2, 100_000);
+        assert_noop!(
+            Feeless::set_status_batch(
+                RuntimeOrigin::signed(1),
+                vec![(1, crate::Status::Unlimited)].try_into().unwrap()
+            ),
+            frame_support::error::BadOrigin
+        );
+        assert_ok!(Feeless::set_status_batch(
+            RawOrigin::Root.into(),
+            vec![
+                (1, crate::Status::Unlimited),
+                (10_000, crate::Status::Unlimited),
+                (2, crate::Status::Unlimited),
+            ]
+            .try_into()
+            .unwrap()
+        ));
+        assert_eq!(
+            frame_system::Account::<Test>::get(1).data.rate.status,
+            crate::Status::Unlimited
+        );
+        assert_eq!(
+            frame_system::Account::<Test>::get(2).data.rate.status,
+            crate::Status::Unlimited
+        );
+        assert!(!frame_system::Account::<Test>::contains_key(10_000));
+        assert_eq!(
+            crate::PendingStatus::<Test>::get(10_000),
+            Some(crate::Status::Unlimited)
+        );
+        System::assert_has_event(
+            crate::Event::StatusChanged {
+                who: 2,
+                status: crate::Status::Unlimited,
+            }
+            .into(),
+        );
+        System::assert_last_event(
+            crate::Event::StatusBatchSet {
+                updated: 2,
+                pending: 1,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn set_status_batch_too_many() {
+    let statuses = |n: u32| {
+        (0..n as u64)
+            .map(|who| (who, crate::Status::Unlimited))
+            .collect::<Vec<_>>()
+    };
+    let max = <Test as crate::Config>::MaxStatusBatch::get();
+    // The call index of `set_status_batch` followed by its statuses.
+    let encoded = |n| (1u8, statuses(n)).encode();
+
+    assert!(crate::Call::<Test>::decode(&mut &encoded(max)[..]).is_ok());
+    assert!(crate::Call::<Test>::decode(&mut &encoded(max + 1)[..]).is_err());
+}
+
+#[test]
+fn genesis_config_works() {
+    let mut ext: sp_io::TestExternalities = RuntimeGenesisConfig {
+        balances: pallet_balances::GenesisConfig {
+            balances: vec![(1, 100_000)],
+            ..Default::default()
+        },
+        feeless: crate::GenesisConfig {
+            statuses: vec![(1, crate::Status::Unlimited), (2, crate::Status::Tier(1))],
+            limits: Some(crate::Limits {
+                max_tx: 2,
+                max_size: 100,
+            }),
+            tiers: vec![(
+                1,
+                crate::Limits {
+                    max_tx: 10,
+                    max_size: 1_000,
+                },
+            )],
+        },
+        ..Default::default()
+    }
+    .build_storage()
+    .unwrap()
+    .into();
+    ext.execute_with(|| {
+        assert_eq!(
+            frame_system::Account::<Test>::get(1).data.rate.status,
+            crate::Status::Unlimited
+        );
+        assert!(!frame_system::Account::<Test>::contains_key(2));
+        assert_eq!(
+            crate::PendingStatus::<Test>::get(2),
+            Some(crate::Status::Tier(1))
+        );
+        assert_eq!(
+            Feeless::limits(&crate::Status::Limited),
+            Some(crate::Limits {
+                max_tx: 2,
+                max_size: 100,
+            })
+        );
+        assert_eq!(
+            Feeless::limits(&crate::Status::Tier(1)),
+            Some(crate::Limits {
+                max_tx: 10,
+                max_size: 1_000,
+            })
+        );
+        // Undefined tiers fall back to the base limits.
+        assert_eq!(
+            Feeless::limits(&crate::Status::Tier(2)),
+            Feeless::limits(&crate::Status::Limited)
+        );
+        assert_eq!(Feeless::limits(&crate::Status::Unlimited), None);
+    });
+}
+
+#[test]
+fn tier_limits_apply() {
+    new_test_ext().execute_with(|| {
+        crate::Tiers::<Test>::insert(
+            1,
+            crate::Limits {
+                max_tx: 1,
+                max_size: 100,
+            },
+        );
+        Balances::set_balance(&1, 100_000); // Init AccountData
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            1,
+            crate::Status::Tier(1)
+        ));
+
+        let info = DispatchInfo::default();
+        // Above the base size limit but within the tier one.
+        let len = <Test as crate::Config>::MaxSizeByPeriod::get() as usize + 1;
+        assert_ok!(
+            CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, len, 0, |_| Ok(
+                Default::default()
+            ))
+        );
+        assert_err!(
+            CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, 0, 0, |_| Ok(
+                Default::default()
+            )),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+    });
+}
+
+#[test]
+fn status_order() {
+    use crate::Status::*;
+    assert!(Limited < Tier(0));
+    assert!(Tier(0) < Tier(1));
+    assert!(Tier(u8::MAX) < Unlimited);
+}
+
+#[test]
+fn registrar_management() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            Feeless::add_registrar(RuntimeOrigin::signed(1), 1, 1, crate::Status::Unlimited),
+            frame_support::error::BadOrigin
+        );
+        assert_ok!(Feeless::add_registrar(
+            RawOrigin::Root.into(),
+            1,
+            2,
+            crate::Status::Tier(1)
+        ));
+        assert_eq!(
+            crate::Registrars::<Test>::get(1),
+            Some(crate::Registrar {
+                allowance: 2,
+                max_status: crate::Status::Tier(1),
+            })
+        );
+        System::assert_last_event(
+            crate::Event::RegistrarAdded {
+                registrar: 1,
+                allowance: 2,
+                max_status: crate::Status::Tier(1),
+            }
+            .into(),
+        );
+
+        assert_ok!(Feeless::remove_registrar(RawOrigin::Root.into(), 1));
+        assert_eq!(crate::Registrars::<Test>::get(1), None);
+        System::assert_last_event(crate::Event::RegistrarRemoved { registrar: 1 }.into());
+        assert_noop!(
+            Feeless::remove_registrar(RawOrigin::Root.into(), 1),
+            crate::Error::<Test>::NotRegistrar
+        );
+    });
+}
+
+#[test]
+fn registrar_set_status_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Balances::set_balance(&2, 100_000); // Init AccountData
+        Balances::set_balance(&3, 100_000);
+        assert_ok!(Feeless::add_registrar(
+            RawOrigin::Root.into(),
+            1,
+            1,
+            crate::Status::Tier(1)
+        ));
+        assert_noop!(
+            Feeless::registrar_set_status(RuntimeOrigin::signed(4), 2, crate::Status::Tier(0)),
+            crate::Error::<Test>::NotRegistrar
+        );
+        assert_noop!(
+            Feeless::registrar_set_status(RuntimeOrigin::signed(1), 2, crate::Status::Unlimited),
+            crate::Error::<Test>::StatusAboveRegistrarMax
+        );
+
+        assert_ok!(Feeless::registrar_set_status(
+            RuntimeOrigin::signed(1),
+            2,
+            crate::Status::Tier(1)
+        ));
+        assert_eq!(
+            frame_system::Account::<Test>::get(2).data.rate.status,
+            crate::Status::Tier(1)
+        );
+        assert_eq!(crate::Registrars::<Test>::get(1).unwrap().allowance, 0);
+        System::assert_last_event(
+            crate::Event::StatusSetByRegistrar {
+                registrar: 1,
+                who: 2,
+                status: crate::Status::Tier(1),
+            }
+            .into(),
+        );
+
+        // No promotion left, but revoking is free.
+        assert_noop!(
+            Feeless::registrar_set_status(RuntimeOrigin::signed(1), 3, crate::Status::Tier(0)),
+            crate::Error::<Test>::AllowanceExhausted
+        );
+        assert_ok!(Feeless::registrar_set_status(
+            RuntimeOrigin::signed(1),
+            2,
+            crate::Status::Limited
+        ));
+        assert_eq!(
+            frame_system::Account::<Test>::get(2).data.rate.status,
+            crate::Status::Limited
+        );
+
+        // Statuses granted above the registrar's maximum cannot be revoked by it.
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            3,
+            crate::Status::Unlimited
+        ));
+        assert_noop!(
+            Feeless::registrar_set_status(RuntimeOrigin::signed(1), 3, crate::Status::Limited),
+            crate::Error::<Test>::StatusAboveRegistrarMax
+        );
+    });
+}
+
+#[test]
+fn registrar_set_pending_status() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Feeless::add_registrar(
+            RawOrigin::Root.into(),
+            1,
+            1,
+            crate::Status::Tier(0)
+        ));
+        assert_ok!(Feeless::registrar_set_status(
+            RuntimeOrigin::signed(1),
+            10_000,
+            crate::Status::Tier(0)
+        ));
+        assert_eq!(
+            crate::PendingStatus::<Test>::get(10_000),
+            Some(crate::Status::Tier(0))
+        );
+        // Lowering a pending status does not need an allowance.
+        assert_ok!(Feeless::registrar_set_status(
+            RuntimeOrigin::signed(1),
+            10_000,
+            crate::Status::Limited
+        ));
+        assert_eq!(crate::PendingStatus::<Test>::get(10_000), None);
+    });
+}
+
+#[test]
+fn usage_statistics() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let info = DispatchInfo::default();
+        for who in [1, 2, 1] {
+            assert_ok!(CheckRate::<Test>::new().test_run(
+                Some(who).into(),
+                CALL,
+                &info,
+                4,
+                0,
+                |_| Ok(Default::default())
+            ));
+        }
+        // Unsigned transactions are not counted.
+        assert_ok!(CheckRate::<Test>::new().test_run(
+            RawOrigin::Root.into(),
+            CALL,
+            &info,
+            4,
+            0,
+            |_| Ok(Default::default())
+        ));
+        assert_eq!(
+            crate::BlockUsage::<Test>::get(),
+            crate::UsageStats {
+                active_accounts: 2,
+                transactions: 3,
+                bytes: 12,
+                accounts_at_quota: 0,
+            }
+        );
+
+        Feeless::on_finalize(1);
+        assert_eq!(
+            crate::LastBlockUsage::<Test>::get(),
+            crate::UsageStats {
+                active_accounts: 2,
+                transactions: 3,
+                bytes: 12,
+                accounts_at_quota: 0,
+            }
+        );
+        assert_eq!(crate::BlockUsage::<Test>::get(), Default::default());
+        assert_eq!(crate::BlockActiveAccounts::<Test>::iter().count(), 0);
+    });
+}
+
+#[test]
+fn quota_reached_once_per_period() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let info = DispatchInfo::default();
+        for _ in 0..<Test as crate::Config>::MaxTxByPeriod::get() {
+            assert_ok!(CheckRate::<Test>::new().test_run(
+                Some(1).into(),
+                CALL,
+                &info,
+                0,
+                0,
+                |_| Ok(Default::default())
+            ));
+        }
+        System::assert_last_event(crate::Event::QuotaReached { who: 1 }.into());
+        assert_eq!(crate::BlockUsage::<Test>::get().accounts_at_quota, 1);
+        assert_eq!(
+            System::events()
+                .iter()
+                .filter(|record| record.event
+                    == RuntimeEvent::Feeless(crate::Event::QuotaReached { who: 1 }))
+                .count(),
+            1
+        );
+
+        // A new period starts with a fresh quota.
+        System::set_block_number(1 + <Test as crate::Config>::Period::get() as u64);
+        System::reset_events();
+        assert_ok!(
+            CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, 0, 0, |_| Ok(
+                Default::default()
+            ))
+        );
+        assert!(System::events().is_empty());
+    });
+}
+
+#[test]
+fn quota_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Balances::set_balance(&1, 100_000); // Init AccountData
+        let period = <Test as crate::Config>::Period::get() as u64;
+        assert_eq!(
+            Feeless::quota(&1),
+            crate::Quota {
+                status: crate::Status::Limited,
+                remaining_tx: Some(<Test as crate::Config>::MaxTxByPeriod::get()),
+                remaining_size: Some(<Test as crate::Config>::MaxSizeByPeriod::get()),
+                resets_at: period,
+            }
+        );
+
+        let info = DispatchInfo::default();
+        System::set_block_number(2);
+        assert_ok!(
+            CheckRate::<Test>::new().test_run(Some(1).into(), CALL, &info, 10, 0, |_| Ok(
+                Default::default()
+            ))
+        );
+        assert_eq!(
+            Feeless::quota(&1),
+            crate::Quota {
+                status: crate::Status::Limited,
+                remaining_tx: Some(<Test as crate::Config>::MaxTxByPeriod::get() - 1),
+                remaining_size: Some(<Test as crate::Config>::MaxSizeByPeriod::get() - 10),
+                resets_at: period,
+            }
+        );
+
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            1,
+            crate::Status::Unlimited
+        ));
+        assert_eq!(Feeless::quota(&1).remaining_tx, None);
+    });
+}
+
+#[test]
+fn prepare_rechecks_in_block_state() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo::default();
+        let (_, val, origin) = CheckRate::<Test>::new()
+            .validate(
+                Some(1).into(),
+                CALL,
+                &info,
+                0,
+                (),
+                &TxBaseImplication(CALL),
+                TransactionSource::External,
+            )
+            .unwrap();
+
+        // Transactions applied since validation used up the quota.
+        frame_system::Account::<Test>::mutate(1, |account| {
+            account.data.rate.tx_since_last = <Test as crate::Config>::MaxTxByPeriod::get()
+        });
+        assert!(matches!(
+            CheckRate::<Test>::new().prepare(val, &origin, CALL, &info, 0),
+            Err(TransactionValidityError::Invalid(
+                InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED)
+            ))
+        ));
+    });
+}
+
+#[test]
+fn quota_enforced_within_a_block() {
+    new_test_ext().execute_with(|| {
+        // Only the transaction count matters here.
+        crate::BaseLimits::<Test>::put(crate::Limits {
+            max_tx: <Test as crate::Config>::MaxTxByPeriod::get(),
+            max_size: u32::MAX,
+        });
+        Executive::initialize_block(&Header::new(
+            1,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        ));
+        let remark = |who: u64| {
+            UncheckedExtrinsic::new_signed(
+                RuntimeCall::System(frame_system::Call::remark { remark: vec![] }),
+                who,
+                UintAuthorityId(who),
+                CheckRate::<Test>::new(),
+            )
+        };
+
+        for _ in 0..<Test as crate::Config>::MaxTxByPeriod::get() {
+            assert_ok!(Executive::apply_extrinsic(remark(1)).unwrap());
+        }
+        assert_err!(
+            Executive::apply_extrinsic(remark(1)),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        // Other accounts are not affected.
+        assert_ok!(Executive::apply_extrinsic(remark(2)).unwrap());
+        assert_eq!(
+            frame_system::Account::<Test>::get(1)
+                .data
+                .rate
+                .tx_since_last,
+            <Test as crate::Config>::MaxTxByPeriod::get()
+        );
+    });
+}
+
+#[test]
+fn last_block_ahead_is_an_expired_period() {
+    new_test_ext().execute_with(|| {
+        let mut data = crate::AccountData::<u64, u64> {
+            rate: crate::Rate {
+                last_block: 100,
+                tx_since_last: <Test as crate::Config>::MaxTxByPeriod::get(),
+                size_since_last: 0,
+                status: crate::Status::Limited,
+            },
+            ..Default::default()
+        };
+        assert!(RateLimiter::<Test>::is_allowed(&data, 5, 1));
+        RateLimiter::<Test>::update_rate(&mut data, 5, 1);
+        assert_eq!(data.rate.last_block, 5);
+        assert_eq!(data.rate.tx_since_last, 1);
+        assert_eq!(data.rate.size_since_last, 1);
+    });
+}
+
+#[test]
+fn counters_saturate() {
+    new_test_ext().execute_with(|| {
+        let mut data = crate::AccountData::<u64, u64> {
+            rate: crate::Rate {
+                last_block: u64::MAX,
+                tx_since_last: u32::MAX,
+                size_since_last: u32::MAX,
+                status: crate::Status::Unlimited,
+            },
+            ..Default::default()
+        };
+        RateLimiter::<Test>::update_rate(&mut data, u64::MAX, u32::MAX);
+        assert_eq!(data.rate.tx_since_last, u32::MAX);
+        assert_eq!(data.rate.size_since_last, u32::MAX);
+        frame_system::Pallet::<Test>::set_block_number(u64::MAX);
+        assert_eq!(Feeless::quota(&1).resets_at, u64::MAX);
+    });
+}
+
+proptest! {
+    #[test]
+    fn rate_limiter_invariants(
+        status in prop_oneof![
+            Just(crate::Status::Limited),
+            Just(crate::Status::Unlimited),
+            any::<u8>().prop_map(crate::Status::Tier),
+        ],
+        ops in prop::collection::vec((any::<u64>(), any::<u32>()), 1..64),
+    ) {
+        new_test_ext().execute_with(|| {
+            crate::Tiers::<Test>::insert(1, crate::Limits { max_tx: 0, max_size: 0 });
+            crate::Tiers::<Test>::insert(2, crate::Limits { max_tx: u32::MAX, max_size: u32::MAX });
+            let limits = Feeless::limits(&status);
+            let mut data = crate::AccountData::<u64, u64>::default();
+            data.rate.status = status;
+
+            for (b, len) in ops {
+                let allowed = RateLimiter::<Test>::is_allowed(&data, b, len);
+                let exhausted = RateLimiter::<Test>::is_exhausted(&data, b);
+                let previous = data.rate.clone();
+                RateLimiter::<Test>::update_rate(&mut data, b, len);
+
+                // Unlimited accounts are never throttled.
+                prop_assert!(limits.is_some() || allowed);
+                // An exhausted quota rejects every non-empty transaction.
+                prop_assert!(!(exhausted && allowed && len > 0));
+                // The period never starts after the current block.
+                prop_assert!(data.rate.last_block <= b);
+                prop_assert!(data.rate.tx_since_last >= 1);
+                prop_assert!(data.rate.size_since_last >= len);
+                if data.rate.last_block == previous.last_block && b >= previous.last_block {
+                    prop_assert!(data.rate.tx_since_last >= previous.tx_since_last);
+                    prop_assert!(data.rate.size_since_last >= previous.size_since_last);
+                } else {
+                    prop_assert_eq!(data.rate.last_block, b);
+                    prop_assert_eq!(data.rate.tx_since_last, 1);
+                    prop_assert_eq!(data.rate.size_since_last, len);
+                }
+                // An allowed transaction never takes the account past its limits.
+                if let (true, Some(limits)) = (allowed, &limits) {
+                    prop_assert!(data.rate.tx_since_last <= limits.max_tx);
+                    prop_assert!(data.rate.size_since_last <= limits.max_size);
+                    prop_assert!(len <= <Test as crate::Config>::MaxSingleTxSize::get());
+                }
+            }
+            Ok(())
+        })?;
+    }
+}
+
+#[test]
+fn exempt_calls_bypass_rate_limit() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo::default();
+        let run = |call: &RuntimeCall| {
+            CheckRate::<Test>::new().test_run(Some(1).into(), call, &info, 0, 0, |_| {
+                Ok(Default::default())
+            })
+        };
+        for _ in 0..<Test as crate::Config>::MaxTxByPeriod::get() {
+            assert_ok!(run(CALL));
+        }
+        assert_err!(
+            run(CALL),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+
+        let exempt = RuntimeCall::System(frame_system::Call::remark_with_event { remark: vec![] });
+        assert_ok!(run(&exempt));
+        // Exempt calls are not counted.
+        assert_eq!(
+            frame_system::Account::<Test>::get(1)
+                .data
+                .rate
+                .tx_since_last,
+            <Test as crate::Config>::MaxTxByPeriod::get()
+        );
+        assert_eq!(crate::BlockUsage::<Test>::get().transactions, 5);
+    });
+}
+
+#[test]
+fn nested_exempt_calls() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo::default();
+        let run = |call: &RuntimeCall| {
+            CheckRate::<Test>::new().test_run(Some(1).into(), call, &info, 0, 0, |_| {
+                Ok(Default::default())
+            })
+        };
+        for _ in 0..<Test as crate::Config>::MaxTxByPeriod::get() {
+            assert_ok!(run(CALL));
+        }
+
+        let exempt = RuntimeCall::System(frame_system::Call::remark_with_event { remark: vec![] });
+        let batch = |calls| RuntimeCall::Utility(pallet_utility::Call::batch { calls });
+        let batch_all = |calls| RuntimeCall::Utility(pallet_utility::Call::batch_all { calls });
+
+        assert_ok!(run(&batch(vec![exempt.clone(), exempt.clone()])));
+        assert_ok!(run(&batch_all(vec![
+            exempt.clone(),
+            batch(vec![exempt.clone()])
+        ])));
+        assert_err!(
+            run(&batch(vec![])),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        // A single non-exempt call anywhere in the tree makes the whole call limited.
+        assert_err!(
+            run(&batch(vec![exempt.clone(), CALL.clone()])),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        assert_err!(
+            run(&batch_all(vec![exempt.clone(), batch(vec![CALL.clone()])])),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+    });
+}
+
+#[test]
+fn exempt_calls_depend_on_signer() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo::default();
+        let run = |who: u64, call: &RuntimeCall| {
+            CheckRate::<Test>::new().test_run(Some(who).into(), call, &info, 0, 0, |_| {
+                Ok(Default::default())
+            })
+        };
+        for who in [1, PRIVILEGED] {
+            for _ in 0..<Test as crate::Config>::MaxTxByPeriod::get() {
+                assert_ok!(run(who, CALL));
+            }
+        }
+
+        let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+        let batch = RuntimeCall::Utility(pallet_utility::Call::batch {
+            calls: vec![remark.clone()],
+        });
+        assert_ok!(run(PRIVILEGED, &remark));
+        assert_ok!(run(PRIVILEGED, &batch));
+        for call in [&remark, &batch] {
+            assert_err!(
+                run(1, call),
+                TransactionValidityError::Invalid(InvalidTransaction::Custom(
+                    crate::QUOTA_EXHAUSTED
+                ))
+            );
+        }
+    });
+}
+
+#[test]
+fn set_stamp_difficulty_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            Feeless::set_stamp_difficulty(RuntimeOrigin::signed(1), 8),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Feeless::set_stamp_difficulty(RawOrigin::Root.into(), 8));
+        assert_eq!(crate::StampDifficulty::<Test>::get(), 8);
+        System::assert_last_event(crate::Event::StampDifficultySet { difficulty: 8 }.into());
+    });
+}
+
+/// Finds a valid stamp for `who` sending `CALL` through `test_run`.
+fn mine_stamp(who: u64) -> u64 {
+    // `test_run` uses extension version 0.
+    let payload = TxBaseImplication((0u8, CALL));
+    (0..)
+        .find(|nonce| Feeless::is_valid_stamp(&who, &payload, *nonce))
+        .unwrap()
+}
+
+#[test]
+fn stamp_passes_beyond_quota() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo::default();
+        let run = |ext: CheckRate<Test>, len| {
+            ext.test_run(Some(1).into(), CALL, &info, len, 0, |_| {
+                Ok(Default::default())
+            })
+        };
+        for _ in 0..<Test as crate::Config>::MaxTxByPeriod::get() {
+            assert_ok!(run(CheckRate::new(), 0));
+        }
+        assert_ok!(Feeless::set_stamp_difficulty(RawOrigin::Root.into(), 8));
+        let nonce = mine_stamp(1);
+
+        assert_err!(
+            run(CheckRate::new(), 0),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        // A stamp that does not match the payload is rejected.
+        let bad = (0..)
+            .find(|n| !Feeless::is_valid_stamp(&1, &TxBaseImplication((0u8, CALL)), *n))
+            .unwrap();
+        assert_err!(
+            run(CheckRate::with_stamp(bad), 0),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        // The stamp does not lift the cap on single transactions.
+        assert_err!(
+            run(
+                CheckRate::with_stamp(nonce),
+                <Test as crate::Config>::MaxSingleTxSize::get() as usize + 1
+            ),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        assert_ok!(run(CheckRate::with_stamp(nonce), 0));
+        // Stamped transactions are still counted.
+        assert_eq!(
+            frame_system::Account::<Test>::get(1)
+                .data
+                .rate
+                .tx_since_last,
+            <Test as crate::Config>::MaxTxByPeriod::get() + 1
+        );
+    });
+}
+
+#[test]
+fn stamps_disabled_by_default() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo::default();
+        for _ in 0..<Test as crate::Config>::MaxTxByPeriod::get() {
+            assert_ok!(CheckRate::<Test>::new().test_run(
+                Some(1).into(),
+                CALL,
+                &info,
+                0,
+                0,
+                |_| Ok(Default::default())
+            ));
+        }
+        assert!(!Feeless::is_valid_stamp(
+            &1,
+            &TxBaseImplication((0u8, CALL)),
+            0
+        ));
+        assert_err!(
+            CheckRate::<Test>::with_stamp(0).test_run(Some(1).into(), CALL, &info, 0, 0, |_| Ok(
+                Default::default()
+            )),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+    });
+}
+
+#[test]
+fn issuer_management() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            Feeless::add_issuer(RuntimeOrigin::signed(1), 10, 3),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Feeless::add_issuer(RawOrigin::Root.into(), 10, 3));
+        assert_eq!(crate::Issuers::<Test>::get(10), Some(3));
+        System::assert_last_event(
+            crate::Event::IssuerAdded {
+                issuer: 10,
+                max_tier: 3,
+            }
+            .into(),
+        );
+        assert_ok!(Feeless::remove_issuer(RawOrigin::Root.into(), 10));
+        assert!(!crate::Issuers::<Test>::contains_key(10));
+        System::assert_last_event(crate::Event::IssuerRemoved { issuer: 10 }.into());
+        assert_noop!(
+            Feeless::remove_issuer(RawOrigin::Root.into(), 10),
+            crate::Error::<Test>::UnknownIssuer
+        );
+    });
+}
+
+fn attestation(who: u64, tier: u8, nonce: u32) -> crate::Attestation<u64, u64> {
+    crate::Attestation {
+        who,
+        issuer: 10,
+        tier,
+        expires_at: 5,
+        nonce,
+    }
+}
+
+fn sign(attestation: &crate::Attestation<u64, u64>) -> TestSignature {
+    TestSignature(
+        attestation.issuer,
+        Feeless::attestation_payload(attestation),
+    )
+}
+
+#[test]
+fn claim_status_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Balances::set_balance(&1, 100_000); // Init AccountData
+        assert_ok!(Feeless::add_issuer(RawOrigin::Root.into(), 10, 3));
+
+        let first = attestation(1, 2, 0);
+        assert_ok!(Feeless::claim_status(
+            RuntimeOrigin::signed(1),
+            first.clone(),
+            sign(&first)
+        ));
+        assert_eq!(
+            frame_system::Account::<Test>::get(1).data.rate.status,
+            crate::Status::Tier(2)
+        );
+        assert_eq!(crate::ClaimNonces::<Test>::get(1), 1);
+        System::assert_last_event(
+            crate::Event::StatusClaimed {
+                who: 1,
+                issuer: 10,
+                status: crate::Status::Tier(2),
+            }
+            .into(),
+        );
+
+        // An attestation cannot be claimed twice, even after a demotion.
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            1,
+            crate::Status::Limited
+        ));
+        assert_noop!(
+            Feeless::claim_status(RuntimeOrigin::signed(1), first.clone(), sign(&first)),
+            crate::Error::<Test>::StaleAttestation
+        );
+        let second = attestation(1, 3, 1);
+        assert_ok!(Feeless::claim_status(
+            RuntimeOrigin::signed(1),
+            second.clone(),
+            sign(&second)
+        ));
+        assert_eq!(
+            frame_system::Account::<Test>::get(1).data.rate.status,
+            crate::Status::Tier(3)
+        );
+    });
+}
+
+#[test]
+fn claim_status_rejects_invalid_attestations() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Balances::set_balance(&1, 100_000); // Init AccountData
+        let claim = |attestation: crate::Attestation<u64, u64>, signature| {
+            Feeless::claim_status(RuntimeOrigin::signed(1), attestation, signature)
+        };
+
+        let valid = attestation(1, 2, 0);
+        assert_noop!(
+            claim(valid.clone(), sign(&valid)),
+            crate::Error::<Test>::UnknownIssuer
+        );
+        assert_ok!(Feeless::add_issuer(RawOrigin::Root.into(), 10, 2));
+
+        let other = attestation(2, 2, 0);
+        assert_noop!(
+            claim(other.clone(), sign(&other)),
+            crate::Error::<Test>::AttestationNotForCaller
+        );
+        let too_high = attestation(1, 3, 0);
+        assert_noop!(
+            claim(too_high.clone(), sign(&too_high)),
+            crate::Error::<Test>::TierAboveIssuerMax
+        );
+        let future = attestation(1, 2, 1);
+        assert_noop!(
+            claim(future.clone(), sign(&future)),
+            crate::Error::<Test>::StaleAttestation
+        );
+        // Signed by another key, or for other content.
+        assert_noop!(
+            claim(
+                valid.clone(),
+                TestSignature(11, Feeless::attestation_payload(&valid))
+            ),
+            crate::Error::<Test>::BadAttestationSignature
+        );
+        assert_noop!(
+            claim(valid.clone(), sign(&attestation(1, 1, 0))),
+            crate::Error::<Test>::BadAttestationSignature
+        );
+
+        System::set_block_number(6);
+        assert_noop!(
+            claim(valid.clone(), sign(&valid)),
+            crate::Error::<Test>::AttestationExpired
+        );
+        System::set_block_number(5);
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            1,
+            crate::Status::Unlimited
+        ));
+        assert_noop!(
+            claim(valid.clone(), sign(&valid)),
+            crate::Error::<Test>::NotAnUpgrade
+        );
+    });
+}
+
+#[test]
+fn set_reputation_rules_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let rules: BoundedVec<_, _> = vec![crate::ReputationRule {
+            min_age: 0,
+            min_clean_periods: 2,
+            tier: 1,
+        }]
+        .try_into()
+        .unwrap();
+        assert_noop!(
+            Feeless::set_reputation_rules(RuntimeOrigin::signed(1), rules.clone()),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Feeless::set_reputation_rules(
+            RawOrigin::Root.into(),
+            rules.clone()
+        ));
+        assert_eq!(crate::ReputationRules::<Test>::get(), rules);
+        System::assert_last_event(crate::Event::ReputationRulesSet { rules: 1 }.into());
+    });
+}
+
+/// Sends `count` transactions from `who` at block `b`.
+fn transact(who: u64, b: u64, count: u32) {
+    System::set_block_number(b);
+    for _ in 0..count {
+        assert_ok!(CheckRate::<Test>::new().test_run(
+            Some(who).into(),
+            CALL,
+            &DispatchInfo::default(),
+            0,
+            0,
+            |_| Ok(Default::default())
+        ));
+    }
+}
+
+fn status_of(who: u64) -> crate::Status {
+    frame_system::Account::<Test>::get(who).data.rate.status
+}
+
+#[test]
+fn reputation_promotes_and_demotes() {
+    new_test_ext().execute_with(|| {
+        let period = <Test as crate::Config>::Period::get() as u64;
+        let max_tx = <Test as crate::Config>::MaxTxByPeriod::get();
+        assert_ok!(Feeless::set_reputation_rules(
+            RawOrigin::Root.into(),
+            vec![
+                crate::ReputationRule {
+                    min_age: 0,
+                    min_clean_periods: 2,
+                    tier: 1,
+                },
+                crate::ReputationRule {
+                    min_age: 4 * period,
+                    min_clean_periods: 2,
+                    tier: 2,
+                },
+            ]
+            .try_into()
+            .unwrap()
+        ));
+
+        transact(1, 1, 1);
+        assert_eq!(
+            crate::Reputations::<Test>::get(1),
+            Some(crate::Reputation {
+                first_seen: 1,
+                clean_periods: 0,
+                period_exhausted: false,
+                tier: None,
+            })
+        );
+        transact(1, 1 + period, 1);
+        assert_eq!(status_of(1), crate::Status::Limited);
+        // Two clean periods.
+        transact(1, 1 + 2 * period, 1);
+        assert_eq!(status_of(1), crate::Status::Tier(1));
+        System::assert_has_event(
+            crate::Event::StatusChanged {
+                who: 1,
+                status: crate::Status::Tier(1),
+            }
+            .into(),
+        );
+        // Old enough for the second rule.
+        transact(1, 1 + 4 * period, max_tx);
+        assert_eq!(status_of(1), crate::Status::Tier(2));
+        assert!(crate::Reputations::<Test>::get(1).unwrap().period_exhausted);
+        // The quota was used up in the last period.
+        transact(1, 1 + 5 * period, 1);
+        assert_eq!(status_of(1), crate::Status::Limited);
+        assert_eq!(
+            crate::Reputations::<Test>::get(1),
+            Some(crate::Reputation {
+                first_seen: 1,
+                clean_periods: 0,
+                period_exhausted: false,
+                tier: None,
+            })
+        );
+    });
+}
+
+#[test]
+fn reputation_leaves_other_statuses_alone() {
+    new_test_ext().execute_with(|| {
+        let period = <Test as crate::Config>::Period::get() as u64;
+        assert_ok!(Feeless::set_reputation_rules(
+            RawOrigin::Root.into(),
+            vec![crate::ReputationRule {
+                min_age: 0,
+                min_clean_periods: 1,
+                tier: 1,
+            }]
+            .try_into()
+            .unwrap()
+        ));
+        Balances::set_balance(&1, 100_000); // Init AccountData
+        Balances::set_balance(&2, 100_000); // Init AccountData
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            1,
+            crate::Status::Unlimited
+        ));
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            2,
+            crate::Status::Tier(5)
+        ));
+        for b in [1, 1 + period, 1 + 2 * period] {
+            transact(1, b, 1);
+            transact(2, b, 1);
+        }
+        assert_eq!(status_of(1), crate::Status::Unlimited);
+        assert_eq!(status_of(2), crate::Status::Tier(5));
+
+        // An account demoted by an admin is not promoted again.
+        transact(3, 1, 1);
+        transact(3, 1 + period, 1);
+        assert_eq!(status_of(3), crate::Status::Tier(1));
+        assert_ok!(Feeless::set_status(
+            RawOrigin::Root.into(),
+            3,
+            crate::Status::Limited
+        ));
+        transact(3, 1 + 2 * period, 1);
+        assert_eq!(status_of(3), crate::Status::Limited);
+    });
+}
+
+fn as_derivative(index: u16, call: RuntimeCall) -> RuntimeCall {
+    RuntimeCall::Utility(pallet_utility::Call::as_derivative {
+        index,
+        call: Box::new(call),
+    })
+}
+
+fn tx_count(who: u64) -> u32 {
+    frame_system::Account::<Test>::get(who)
+        .data
+        .rate
+        .tx_since_last
+}
+
+#[test]
+fn derivative_charge_modes() {
+    let derivative = Utility::derivative_account_id(1, 0);
+    let call = as_derivative(0, CALL.clone());
+    let run = |call: &RuntimeCall| {
+        CheckRate::<Test>::new().test_run(
+            Some(1).into(),
+            call,
+            &DispatchInfo::default(),
+            0,
+            0,
+            |_| Ok(Default::default()),
+        )
+    };
+    for (mode, signer, effective) in [
+        (crate::DerivativeCharge::Signer, 1, 0),
+        (crate::DerivativeCharge::Effective, 0, 1),
+        (crate::DerivativeCharge::Both, 1, 1),
+    ] {
+        DerivativeChargeMode::set(mode);
+        new_test_ext().execute_with(|| {
+            Balances::set_balance(&derivative, 100_000);
+            assert_ok!(run(&call));
+            assert_eq!(tx_count(1), signer);
+            assert_eq!(tx_count(derivative), effective);
+        });
+    }
+    DerivativeChargeMode::set(crate::DerivativeCharge::Both);
+}
+
+#[test]
+fn weight_counts_charged_accounts() {
+    let weight = |call: &RuntimeCall| CheckRate::<Test>::new().weight(call);
+    let single = weight(CALL);
+    assert!(single.all_gt(Default::default()));
+    // The signer and the derivative account.
+    assert_eq!(
+        weight(&as_derivative(0, CALL.clone())),
+        single.saturating_mul(2)
+    );
+    assert_eq!(
+        weight(&RuntimeCall::Utility(pallet_utility::Call::batch {
+            calls: vec![
+                as_derivative(0, CALL.clone()),
+                as_derivative(1, CALL.clone())
+            ],
+        })),
+        single.saturating_mul(3)
+    );
+}
+
+#[test]
+fn derivative_quota_is_enforced() {
+    new_test_ext().execute_with(|| {
+        let derivative = Utility::derivative_account_id(1, 0);
+        Balances::set_balance(&derivative, 100_000);
+        Balances::set_balance(&Utility::derivative_account_id(1, 1), 100_000);
+        let run = |call: &RuntimeCall| {
+            CheckRate::<Test>::new().test_run(
+                Some(1).into(),
+                call,
+                &DispatchInfo::default(),
+                0,
+                0,
+                |_| Ok(Default::default()),
+            )
+        };
+        for _ in 0..<Test as crate::Config>::MaxTxByPeriod::get() {
+            assert_ok!(<Feeless as crate::ChargeQuota<u64>>::charge(&derivative, 0));
+        }
+        assert_noop!(
+            <Feeless as crate::ChargeQuota<u64>>::charge(&derivative, 0),
+            crate::Error::<Test>::QuotaExhausted
+        );
+
+        // The signer still has quota, but not the account it acts as, even within a batch.
+        assert_ok!(run(CALL));
+        assert_err!(
+            run(&as_derivative(0, CALL.clone())),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        assert_err!(
+            run(&RuntimeCall::Utility(pallet_utility::Call::batch {
+                calls: vec![CALL.clone(), as_derivative(0, CALL.clone())]
+            })),
+            TransactionValidityError::Invalid(InvalidTransaction::Custom(crate::QUOTA_EXHAUSTED))
+        );
+        // Another derivative has its own quota.
+        assert_ok!(run(&as_derivative(1, CALL.clone())));
+        assert_eq!(tx_count(1), 2);
+        assert_eq!(tx_count(Utility::derivative_account_id(1, 1)), 1);
+    });
+}
+
+#[test]
+fn nested_derivatives_charge_the_innermost_account() {
+    new_test_ext().execute_with(|| {
+        let inner = Utility::derivative_account_id(Utility::derivative_account_id(1, 0), 2);
+        Balances::set_balance(&inner, 100_000);
+        assert_ok!(CheckRate::<Test>::new().test_run(
+            Some(1).into(),
+            &as_derivative(0, as_derivative(2, CALL.clone())),
+            &DispatchInfo::default(),
+            0,
+            0,
+            |_| Ok(Default::default())
+        ));
+        assert_eq!(tx_count(1), 1);
+        assert_eq!(tx_count(inner), 1);
+        assert_eq!(tx_count(Utility::derivative_account_id(1, 0)), 0);
+    });
+}
+
+#[test]
+fn missing_accounts_are_not_created() {
+    let derivative = Utility::derivative_account_id(1, 0);
+    let call = as_derivative(0, CALL.clone());
+    for mode in [
+        crate::DerivativeCharge::Effective,
+        crate::DerivativeCharge::Both,
+    ] {
+        DerivativeChargeMode::set(mode);
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            // The signer is charged in place of the derivative account.
+            assert_ok!(CheckRate::<Test>::new().test_run(
+                Some(1).into(),
+                &call,
+                &DispatchInfo::default(),
+                0,
+                0,
+                |_| Ok(Default::default())
+            ));
+            assert_eq!(tx_count(1), 1);
+            assert!(!frame_system::Account::<Test>::contains_key(derivative));
+
+            assert_ok!(<Feeless as crate::ChargeQuota<u64>>::charge(&derivative, 0));
+            assert!(!frame_system::Account::<Test>::contains_key(derivative));
+            assert_eq!(crate::BlockUsage::<Test>::get().transactions, 1);
+        });
+    }
+    DerivativeChargeMode::set(crate::DerivativeCharge::Both);
+}
+
+#[test]
+fn accounts_removed_by_the_call_are_not_recreated() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(CheckRate::<Test>::new().test_run(
+            Some(1).into(),
+            CALL,
+            &DispatchInfo::default(),
+            0,
+            0,
+            |_| {
+                Balances::set_balance(&1, 0);
+                Ok(Default::default())
+            }
+        ));
+        assert!(!frame_system::Account::<Test>::contains_key(1));
+    });
+}
+
+#[test]
+fn charge_counts_usage() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(<Feeless as crate::ChargeQuota<u64>>::charge(&1, 10));
+        assert_eq!(
+            frame_system::Account::<Test>::get(1)
+                .data
+                .rate
+                .size_since_last,
+            10
         );
+        assert_eq!(crate::BlockUsage::<Test>::get().transactions, 1);
     });
 }
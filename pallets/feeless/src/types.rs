@@ -23,10 +23,13 @@
 //
 // You should have received a copy of the GPL along with this program.
 // If not, see <http://www.gnu.org/licenses/>.
+use alloc::vec::Vec;
 use codec::{Decode, DecodeWithMemTracking, Encode, MaxEncodedLen};
+use frame_support::{traits::Contains, weights::Weight};
 use frame_system::pallet_prelude::BlockNumberFor;
 use scale_info::TypeInfo;
-use sp_runtime::RuntimeDebug;
+use serde::{Deserialize, Serialize};
+use sp_runtime::{DispatchResult, RuntimeDebug};
 
 #[derive(
     Encode,
@@ -39,11 +42,188 @@ use sp_runtime::RuntimeDebug;
     RuntimeDebug,
     MaxEncodedLen,
     TypeInfo,
+    Serialize,
+    Deserialize,
 )]
 pub enum Status {
+    /// Subject to the base limits.
     #[default]
     Limited,
+    /// Not rate limited at all.
     Unlimited,
+    /// Subject to the limits of the given tier, or the base limits if the tier is not defined.
+    Tier(u8),
+}
+
+impl Status {
+    /// Position of the status in the `Limited < Tier(0) < .. < Tier(255) < Unlimited` order.
+    fn rank(&self) -> u16 {
+        match self {
+            Status::Limited => 0,
+            Status::Tier(tier) => u16::from(*tier) + 1,
+            Status::Unlimited => u16::MAX,
+        }
+    }
+}
+
+impl PartialOrd for Status {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Status {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+/// A delegated status administrator.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    PartialEq,
+    Eq,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub struct Registrar {
+    /// Number of promotions the registrar can still grant.
+    pub allowance: u32,
+    /// Highest status the registrar can grant or revoke.
+    pub max_status: Status,
+}
+
+/// A credential, signed by an issuer, allowing an account to claim a tier.
+#[derive(Encode, Decode, DecodeWithMemTracking, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct Attestation<AccountId, BlockNumber> {
+    /// Account the attestation was issued to.
+    pub who: AccountId,
+    /// Issuer that signed the attestation.
+    pub issuer: AccountId,
+    /// Tier the account can claim.
+    pub tier: u8,
+    /// Last block at which the attestation can be claimed.
+    pub expires_at: BlockNumber,
+    /// Claim nonce of the account the attestation is valid for.
+    pub nonce: u32,
+}
+
+/// Aggregated rate-limiting usage over a block.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    PartialEq,
+    Eq,
+    Default,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub struct UsageStats {
+    /// Number of distinct signed accounts that transacted.
+    pub active_accounts: u32,
+    /// Number of transactions counted against account quotas.
+    pub transactions: u32,
+    /// Total size (in bytes) of those transactions.
+    pub bytes: u32,
+    /// Number of accounts that reached their quota for the current period.
+    pub accounts_at_quota: u32,
+}
+
+/// What is left of an account's quota in the current period.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    PartialEq,
+    Eq,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+    Serialize,
+    Deserialize,
+)]
+pub struct Quota<BlockNumber> {
+    /// Status of the account.
+    pub status: Status,
+    /// Transactions still allowed in the period, `None` if the account is not rate limited.
+    pub remaining_tx: Option<u32>,
+    /// Bytes still allowed in the period, `None` if the account is not rate limited.
+    pub remaining_size: Option<u32>,
+    /// Block at which a new period starts and the quota is reset.
+    pub resets_at: BlockNumber,
+}
+
+/// Rate limits applied within a period.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    PartialEq,
+    Eq,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+    Serialize,
+    Deserialize,
+)]
+pub struct Limits {
+    /// Maximum number of transactions within a period.
+    pub max_tx: u32,
+    /// Maximum total size of transactions (in bytes) within a period.
+    pub max_size: u32,
+}
+
+/// Usage history of an account, from which it earns a tier.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    PartialEq,
+    Eq,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub struct Reputation<BlockNumber> {
+    /// Block of the first transaction of the account.
+    pub first_seen: BlockNumber,
+    /// Number of consecutive periods the account transacted in without using up its quota.
+    pub clean_periods: u32,
+    /// Whether the account used up its quota in the current period.
+    pub period_exhausted: bool,
+    /// Tier granted by reputation, if the current status of the account is one.
+    pub tier: Option<u8>,
+}
+
+/// Grants `tier` to accounts with enough history.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    PartialEq,
+    Eq,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub struct ReputationRule<BlockNumber> {
+    /// Minimum number of blocks since the first transaction of the account.
+    pub min_age: BlockNumber,
+    /// Minimum number of consecutive clean periods.
+    pub min_clean_periods: u32,
+    /// Tier granted.
+    pub tier: u8,
 }
 
 /// Tracks transaction rates for an account over blocks.
@@ -95,4 +275,95 @@ pub trait RateLimiter<T: frame_system::Config> {
     fn is_allowed(&self, b: BlockNumberFor<T>, size: u32) -> bool;
     /// Updates the rate limiter after a transaction.
     fn update_rate(&mut self, b: BlockNumberFor<T>, size: u32);
+    /// Checks if the quota for the current period is used up, i.e. not even a one byte
+    /// transaction is allowed.
+    fn is_exhausted(&self, b: BlockNumberFor<T>) -> bool {
+        !self.is_allowed(b, 1)
+    }
+    /// Updates the reputation of `who` before a transaction at block `b` is counted, possibly
+    /// changing its status. Does nothing by default.
+    fn update_reputation(&mut self, _who: &T::AccountId, _b: BlockNumberFor<T>) {}
+    /// Counts a transaction of `who` at block `b`, updating its reputation first. Returns
+    /// whether this transaction used up the quota.
+    fn record(&mut self, who: &T::AccountId, b: BlockNumberFor<T>, size: u32) -> bool {
+        self.update_reputation(who, b);
+        let was_exhausted = self.is_exhausted(b);
+        self.update_rate(b, size);
+        !was_exhausted && self.is_exhausted(b)
+    }
+}
+
+/// Accounts charged for a call dispatched on behalf of other accounts.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub enum DerivativeCharge {
+    /// Only the signer.
+    Signer,
+    /// Only the accounts the call is dispatched as, or the signer if there are none.
+    Effective,
+    /// The signer and the accounts the call is dispatched as.
+    Both,
+}
+
+/// Decides which calls bypass `CheckRate`, possibly depending on their signer.
+///
+/// Any `Contains<Call>` exempts the calls it contains whoever signs them.
+pub trait IsExempt<AccountId, Call> {
+    /// Returns whether `call` signed by `who` is exempt.
+    fn is_exempt(who: &AccountId, call: &Call) -> bool;
+
+    /// Returns the most weight `is_exempt` takes for `call`, e.g. to read the accounts the call
+    /// is restricted to, on top of the `check_rate` benchmark.
+    fn weight(_call: &Call) -> Weight {
+        Weight::zero()
+    }
+}
+
+impl<AccountId, Call, C: Contains<Call>> IsExempt<AccountId, Call> for C {
+    fn is_exempt(_: &AccountId, call: &Call) -> bool {
+        C::contains(call)
+    }
+}
+
+/// Resolves the accounts a call is dispatched as when it acts on behalf of other accounts, e.g.
+/// through `utility::as_derivative`.
+pub trait EffectiveAccounts<AccountId, Call> {
+    /// Returns the accounts `call` signed by `who` is dispatched as, other than `who`.
+    fn effective_accounts(who: &AccountId, call: &Call) -> Vec<AccountId>;
+}
+
+impl<AccountId, Call> EffectiveAccounts<AccountId, Call> for () {
+    fn effective_accounts(_: &AccountId, _: &Call) -> Vec<AccountId> {
+        Vec::new()
+    }
+}
+
+/// Charges the quota of accounts only known when a call is dispatched, e.g. the account a proxy
+/// acts for, which `CheckRate` cannot see.
+pub trait ChargeQuota<AccountId> {
+    /// Counts a transaction of `len` bytes against the quota of `who`, or fails if it has no
+    /// quota left for it. Nothing is counted if `who` does not exist.
+    ///
+    /// This is not weighed: callers include the account update, and the `BlockActiveAccounts`
+    /// entry written here and cleared in `on_finalize`, in the weight of their call.
+    fn charge(who: &AccountId, len: u32) -> DispatchResult;
+}
+
+/// Provides signed attestations to the benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper<AccountId, Signature> {
+    /// Returns the account of the issuer used in the benchmarks.
+    fn issuer() -> AccountId;
+    /// Signs `message` as the issuer.
+    fn sign(message: &[u8]) -> Signature;
 }
@@ -1,20 +1,24 @@
 
 //! Autogenerated weights for `pallet_feeless`
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 32.0.0
-//! DATE: 2024-11-26, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
+//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 47.0.0
+//! DATE: 2026-10-18, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
 //! WORST CASE MAP SIZE: `1000000`
-//! HOSTNAME: `bgallois-ms7d43`, CPU: `12th Gen Intel(R) Core(TM) i3-12100F`
-//! WASM-EXECUTION: `Compiled`, CHAIN: `Some("dev")`, DB CACHE: 1024
+//! HOSTNAME: `vm`, CPU: `Intel(R) Xeon(R) Processor`
+//! WASM-EXECUTION: `Compiled`, CHAIN: `Some("dev")`, DB CACHE: `1024`
 
 // Executed Command:
-// target/release/solochain-template-node
+// ./target/release/solochain-template-node
 // benchmark
 // pallet
 // --chain=dev
 // --pallet=pallet_feeless
 // --extrinsic=*
+// --steps=50
+// --repeat=20
+// --wasm-execution=compiled
 // --output=pallets/feeless/src/weights.rs
+// --template=.maintain/frame-weight-template.hbs
 
 #![cfg_attr(rustfmt, rustfmt_skip)]
 #![allow(unused_parens)]
@@ -22,16 +26,318 @@
 #![allow(missing_docs)]
 
 use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
-use frame_system::weights::SubstrateWeight;
 use core::marker::PhantomData;
 
-/// Weight functions needed for pallet_template.
+/// Weight functions needed for `pallet_feeless`.
 pub trait WeightInfo {
 	fn set_status() -> Weight;
+	fn set_status_batch(n: u32, ) -> Weight;
+	fn add_registrar() -> Weight;
+	fn remove_registrar() -> Weight;
+	fn registrar_set_status() -> Weight;
+	fn set_stamp_difficulty() -> Weight;
+	fn add_issuer() -> Weight;
+	fn remove_issuer() -> Weight;
+	fn claim_status() -> Weight;
+	fn set_reputation_rules() -> Weight;
+	fn check_rate() -> Weight;
 }
 
+/// Weights for `pallet_feeless` using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	fn set_status() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `116`
+		//  Estimated: `3607`
+		// Minimum execution time: 29_330_000 picoseconds.
+		Weight::from_parts(31_110_000, 3607)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `System::Account` (r:512 w:0)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	/// Storage: `Account::PendingStatus` (r:0 w:512)
+	/// Proof: `Account::PendingStatus` (`max_values`: None, `max_size`: Some(50), added: 2525, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 512]`.
+	fn set_status_batch(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `990 + n * (2617 ±0)`
+		// Minimum execution time: 36_012_000 picoseconds.
+		Weight::from_parts(37_295_000, 990)
+			// Standard Error: 58_214
+			.saturating_add(Weight::from_parts(17_408_912, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2617).saturating_mul(n.into()))
+	}
+	/// Storage: `Account::Registrars` (r:0 w:1)
+	/// Proof: `Account::Registrars` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	fn add_registrar() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 17_404_000 picoseconds.
+		Weight::from_parts(21_195_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Registrars` (r:1 w:1)
+	/// Proof: `Account::Registrars` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	fn remove_registrar() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `84`
+		//  Estimated: `3519`
+		// Minimum execution time: 29_744_000 picoseconds.
+		Weight::from_parts(31_142_000, 3519)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Registrars` (r:1 w:1)
+	/// Proof: `Account::Registrars` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	fn registrar_set_status() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `200`
+		//  Estimated: `3607`
+		// Minimum execution time: 50_494_000 picoseconds.
+		Weight::from_parts(59_084_000, 3607)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Account::StampDifficulty` (r:0 w:1)
+	/// Proof: `Account::StampDifficulty` (`max_values`: Some(1), `max_size`: Some(1), added: 496, mode: `MaxEncodedLen`)
+	fn set_stamp_difficulty() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 14_433_000 picoseconds.
+		Weight::from_parts(16_057_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Issuers` (r:0 w:1)
+	/// Proof: `Account::Issuers` (`max_values`: None, `max_size`: Some(49), added: 2524, mode: `MaxEncodedLen`)
+	fn add_issuer() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 19_966_000 picoseconds.
+		Weight::from_parts(21_961_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Issuers` (r:1 w:1)
+	/// Proof: `Account::Issuers` (`max_values`: None, `max_size`: Some(49), added: 2524, mode: `MaxEncodedLen`)
+	fn remove_issuer() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `80`
+		//  Estimated: `3514`
+		// Minimum execution time: 29_416_000 picoseconds.
+		Weight::from_parts(33_471_000, 3514)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Issuers` (r:1 w:0)
+	/// Proof: `Account::Issuers` (`max_values`: None, `max_size`: Some(49), added: 2524, mode: `MaxEncodedLen`)
+	/// Storage: `Account::ClaimNonces` (r:1 w:1)
+	/// Proof: `Account::ClaimNonces` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// Storage: `System::BlockHash` (r:1 w:0)
+	/// Proof: `System::BlockHash` (`max_values`: None, `max_size`: Some(44), added: 2519, mode: `MaxEncodedLen`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	fn claim_status() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `226`
+		//  Estimated: `3607`
+		// Minimum execution time: 171_569_000 picoseconds.
+		Weight::from_parts(176_774_000, 3607)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Account::ReputationRules` (r:0 w:1)
+	/// Proof: `Account::ReputationRules` (`max_values`: Some(1), `max_size`: Some(73), added: 568, mode: `MaxEncodedLen`)
+	fn set_reputation_rules() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 8_498_000 picoseconds.
+		Weight::from_parts(13_850_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::StampDifficulty` (r:1 w:0)
+	/// Proof: `Account::StampDifficulty` (`max_values`: Some(1), `max_size`: Some(1), added: 496, mode: `MaxEncodedLen`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	/// Storage: `Account::BaseLimits` (r:1 w:0)
+	/// Proof: `Account::BaseLimits` (`max_values`: Some(1), `max_size`: Some(8), added: 503, mode: `MaxEncodedLen`)
+	/// Storage: `Account::Reputations` (r:1 w:1)
+	/// Proof: `Account::Reputations` (`max_values`: None, `max_size`: Some(59), added: 2534, mode: `MaxEncodedLen`)
+	/// Storage: `Account::ReputationRules` (r:1 w:0)
+	/// Proof: `Account::ReputationRules` (`max_values`: Some(1), `max_size`: Some(73), added: 568, mode: `MaxEncodedLen`)
+	/// Storage: `Account::Tiers` (r:1 w:0)
+	/// Proof: `Account::Tiers` (`max_values`: None, `max_size`: Some(17), added: 2492, mode: `MaxEncodedLen`)
+	/// Storage: `Account::BlockActiveAccounts` (r:1 w:1)
+	/// Proof: `Account::BlockActiveAccounts` (`max_values`: None, `max_size`: Some(48), added: 2523, mode: `MaxEncodedLen`)
+	/// Storage: `Account::BlockUsage` (r:1 w:1)
+	/// Proof: `Account::BlockUsage` (`max_values`: Some(1), `max_size`: Some(16), added: 511, mode: `MaxEncodedLen`)
+	fn check_rate() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `251`
+		//  Estimated: `3607`
+		// Minimum execution time: 45_903_000 picoseconds.
+		Weight::from_parts(47_812_000, 3607)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+}
+
+// For backwards compatibility and tests.
 impl WeightInfo for () {
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
 	fn set_status() -> Weight {
-		Weight::from_parts(3_920_000, 0)
+		// Proof Size summary in bytes:
+		//  Measured:  `116`
+		//  Estimated: `3607`
+		// Minimum execution time: 29_330_000 picoseconds.
+		Weight::from_parts(31_110_000, 3607)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `System::Account` (r:512 w:0)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	/// Storage: `Account::PendingStatus` (r:0 w:512)
+	/// Proof: `Account::PendingStatus` (`max_values`: None, `max_size`: Some(50), added: 2525, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 512]`.
+	fn set_status_batch(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `990 + n * (2617 ±0)`
+		// Minimum execution time: 36_012_000 picoseconds.
+		Weight::from_parts(37_295_000, 990)
+			// Standard Error: 58_214
+			.saturating_add(Weight::from_parts(17_408_912, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2617).saturating_mul(n.into()))
+	}
+	/// Storage: `Account::Registrars` (r:0 w:1)
+	/// Proof: `Account::Registrars` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	fn add_registrar() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 17_404_000 picoseconds.
+		Weight::from_parts(21_195_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Registrars` (r:1 w:1)
+	/// Proof: `Account::Registrars` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	fn remove_registrar() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `84`
+		//  Estimated: `3519`
+		// Minimum execution time: 29_744_000 picoseconds.
+		Weight::from_parts(31_142_000, 3519)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Registrars` (r:1 w:1)
+	/// Proof: `Account::Registrars` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	fn registrar_set_status() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `200`
+		//  Estimated: `3607`
+		// Minimum execution time: 50_494_000 picoseconds.
+		Weight::from_parts(59_084_000, 3607)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Account::StampDifficulty` (r:0 w:1)
+	/// Proof: `Account::StampDifficulty` (`max_values`: Some(1), `max_size`: Some(1), added: 496, mode: `MaxEncodedLen`)
+	fn set_stamp_difficulty() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 14_433_000 picoseconds.
+		Weight::from_parts(16_057_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Issuers` (r:0 w:1)
+	/// Proof: `Account::Issuers` (`max_values`: None, `max_size`: Some(49), added: 2524, mode: `MaxEncodedLen`)
+	fn add_issuer() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 19_966_000 picoseconds.
+		Weight::from_parts(21_961_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Issuers` (r:1 w:1)
+	/// Proof: `Account::Issuers` (`max_values`: None, `max_size`: Some(49), added: 2524, mode: `MaxEncodedLen`)
+	fn remove_issuer() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `80`
+		//  Estimated: `3514`
+		// Minimum execution time: 29_416_000 picoseconds.
+		Weight::from_parts(33_471_000, 3514)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::Issuers` (r:1 w:0)
+	/// Proof: `Account::Issuers` (`max_values`: None, `max_size`: Some(49), added: 2524, mode: `MaxEncodedLen`)
+	/// Storage: `Account::ClaimNonces` (r:1 w:1)
+	/// Proof: `Account::ClaimNonces` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// Storage: `System::BlockHash` (r:1 w:0)
+	/// Proof: `System::BlockHash` (`max_values`: None, `max_size`: Some(44), added: 2519, mode: `MaxEncodedLen`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	fn claim_status() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `226`
+		//  Estimated: `3607`
+		// Minimum execution time: 171_569_000 picoseconds.
+		Weight::from_parts(176_774_000, 3607)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Account::ReputationRules` (r:0 w:1)
+	/// Proof: `Account::ReputationRules` (`max_values`: Some(1), `max_size`: Some(73), added: 568, mode: `MaxEncodedLen`)
+	fn set_reputation_rules() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 8_498_000 picoseconds.
+		Weight::from_parts(13_850_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Account::StampDifficulty` (r:1 w:0)
+	/// Proof: `Account::StampDifficulty` (`max_values`: Some(1), `max_size`: Some(1), added: 496, mode: `MaxEncodedLen`)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Proof: `System::Account` (`max_values`: None, `max_size`: Some(142), added: 2617, mode: `MaxEncodedLen`)
+	/// Storage: `Account::BaseLimits` (r:1 w:0)
+	/// Proof: `Account::BaseLimits` (`max_values`: Some(1), `max_size`: Some(8), added: 503, mode: `MaxEncodedLen`)
+	/// Storage: `Account::Reputations` (r:1 w:1)
+	/// Proof: `Account::Reputations` (`max_values`: None, `max_size`: Some(59), added: 2534, mode: `MaxEncodedLen`)
+	/// Storage: `Account::ReputationRules` (r:1 w:0)
+	/// Proof: `Account::ReputationRules` (`max_values`: Some(1), `max_size`: Some(73), added: 568, mode: `MaxEncodedLen`)
+	/// Storage: `Account::Tiers` (r:1 w:0)
+	/// Proof: `Account::Tiers` (`max_values`: None, `max_size`: Some(17), added: 2492, mode: `MaxEncodedLen`)
+	/// Storage: `Account::BlockActiveAccounts` (r:1 w:1)
+	/// Proof: `Account::BlockActiveAccounts` (`max_values`: None, `max_size`: Some(48), added: 2523, mode: `MaxEncodedLen`)
+	/// Storage: `Account::BlockUsage` (r:1 w:1)
+	/// Proof: `Account::BlockUsage` (`max_values`: Some(1), `max_size`: Some(16), added: 511, mode: `MaxEncodedLen`)
+	fn check_rate() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `251`
+		//  Estimated: `3607`
+		// Minimum execution time: 45_903_000 picoseconds.
+		Weight::from_parts(47_812_000, 3607)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
 	}
 }
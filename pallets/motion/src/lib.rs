@@ -0,0 +1,113 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![doc = include_str!("../README.md")]
+extern crate alloc;
+
+use alloc::boxed::Box;
+use frame_support::{
+    dispatch::{DispatchResultWithPostInfo, GetDispatchInfo, Pays},
+    pallet_prelude::{EnsureOrigin, IsType, Parameter},
+    traits::UnfilteredDispatchable,
+};
+use frame_system::{pallet_prelude::OriginFor, RawOrigin};
+pub use pallet::*;
+use sp_runtime::DispatchResult;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+// The call enum expanded by `#[pallet::call]` trips these lints on the pinned toolchain.
+#[allow(unreachable_patterns, clippy::multiple_bound_locations)]
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching runtime event type.
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        /// The calls a motion can dispatch.
+        type RuntimeCall: Parameter
+            + UnfilteredDispatchable<RuntimeOrigin = Self::RuntimeOrigin>
+            + GetDispatchInfo
+            + From<frame_system::Call<Self>>;
+        /// The origin which may dispatch calls as root, typically a qualified majority of a
+        /// collective.
+        type MotionOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// A type representing the weights required by the dispatchables of this pallet.
+        type WeightInfo: WeightInfo;
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// A motion dispatched a call as root.
+        MotionExecuted { result: DispatchResult },
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Dispatches a call with the root origin.
+        ///
+        /// The outcome of the call is reported by `MotionExecuted`, this call succeeds whatever
+        /// it is.
+        ///
+        /// ## Arguments:
+        /// - `origin`: Must be `MotionOrigin`.
+        /// - `call`: The call to dispatch.
+        #[pallet::call_index(0)]
+        #[pallet::weight({
+            let dispatch_info = call.get_dispatch_info();
+            (
+                T::WeightInfo::dispatch_as_root().saturating_add(dispatch_info.call_weight),
+                dispatch_info.class,
+            )
+        })]
+        pub fn dispatch_as_root(
+            origin: OriginFor<T>,
+            call: Box<<T as Config>::RuntimeCall>,
+        ) -> DispatchResultWithPostInfo {
+            T::MotionOrigin::ensure_origin(origin)?;
+            let result = call.dispatch_bypass_filter(RawOrigin::Root.into());
+            Self::deposit_event(Event::MotionExecuted {
+                result: result.map(|_| ()).map_err(|e| e.error),
+            });
+            Ok(Pays::No.into())
+        }
+    }
+}
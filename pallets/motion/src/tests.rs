@@ -0,0 +1,82 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+use crate::{mock::*, Event};
+use frame_support::{assert_noop, assert_ok, dispatch::Pays};
+use frame_system::RawOrigin;
+use sp_runtime::DispatchError;
+
+fn set_storage() -> Box<RuntimeCall> {
+    Box::new(RuntimeCall::System(frame_system::Call::set_storage {
+        items: vec![(b"key".to_vec(), b"value".to_vec())],
+    }))
+}
+
+#[test]
+fn dispatch_as_root_works() {
+    new_test_ext().execute_with(|| {
+        let post_info =
+            Motion::dispatch_as_root(RawOrigin::Signed(1).into(), set_storage()).unwrap();
+        assert_eq!(post_info.pays_fee, Pays::No);
+        assert_eq!(sp_io::storage::get(b"key").as_deref(), Some(&b"value"[..]));
+        System::assert_last_event(Event::MotionExecuted { result: Ok(()) }.into());
+    })
+}
+
+#[test]
+fn dispatch_as_root_requires_motion_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Motion::dispatch_as_root(RawOrigin::Signed(2).into(), set_storage()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Motion::dispatch_as_root(RawOrigin::Root.into(), set_storage()),
+            DispatchError::BadOrigin
+        );
+        assert_eq!(sp_io::storage::get(b"key"), None);
+    })
+}
+
+#[test]
+fn failed_call_is_reported() {
+    new_test_ext().execute_with(|| {
+        // Root is not the motion origin, so the inner motion fails.
+        let nested = Box::new(RuntimeCall::Motion(crate::Call::dispatch_as_root {
+            call: set_storage(),
+        }));
+        assert_ok!(Motion::dispatch_as_root(
+            RawOrigin::Signed(1).into(),
+            nested
+        ));
+        System::assert_last_event(
+            Event::MotionExecuted {
+                result: Err(DispatchError::BadOrigin),
+            }
+            .into(),
+        );
+        assert_eq!(sp_io::storage::get(b"key"), None);
+    })
+}
@@ -61,6 +61,8 @@ pub mod weights;
 pub use weights::*;
 
 // All pallet logic is defined in its own module and must be annotated by the `pallet` attribute.
+// The call enum expanded by `#[pallet::call]` trips this lint on the pinned toolchain.
+#[allow(unreachable_patterns)]
 #[frame_support::pallet]
 pub mod pallet {
     // Import various useful types required by all FRAME pallets.
@@ -0,0 +1,73 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+use super::*;
+use crate::Pallet;
+use codec::Decode;
+use frame_benchmarking::v2::*;
+use frame_system::RawOrigin;
+use sp_runtime::traits::TrailingZeroInput;
+
+const SEED: u32 = 0;
+
+/// Fills the validator set with `n` validators.
+fn fill<T: Config>(n: u32) {
+    let validators: Vec<T::AccountId> = (0..n).map(|i| account("validator", i, SEED)).collect();
+    Validators::<T>::put(BoundedVec::truncate_from(validators));
+}
+
+#[benchmarks(where T: pallet_session::Config<ValidatorId = <T as frame_system::Config>::AccountId>)]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn add_validator() {
+        fill::<T>(T::MaxValidators::get() - 1);
+        let who: T::AccountId = account("new", 0, SEED);
+        let keys = T::Keys::decode(&mut TrailingZeroInput::zeroes()).unwrap();
+        pallet_session::NextKeys::<T>::insert(&who, keys);
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, who.clone());
+
+        assert!(Validators::<T>::get().contains(&who));
+        assert!(ValidatorsChanged::<T>::get());
+    }
+
+    #[benchmark]
+    fn remove_validator() {
+        let max = T::MaxValidators::get();
+        fill::<T>(max);
+        let who: T::AccountId = account("validator", max - 1, SEED);
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, who.clone());
+
+        assert!(!Validators::<T>::get().contains(&who));
+        assert!(ValidatorsChanged::<T>::get());
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,291 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![doc = include_str!("../README.md")]
+extern crate alloc;
+
+use alloc::vec::Vec;
+use frame_support::{
+    ensure,
+    pallet_prelude::{BoundedVec, EnsureOrigin, IsType, StorageValue, ValueQuery},
+    traits::{BuildGenesisConfig, Get, ValidatorRegistration},
+    weights::Weight,
+};
+use frame_system::pallet_prelude::OriginFor;
+pub use pallet::*;
+use sp_runtime::{traits::Convert, DispatchResult, Perbill};
+use sp_staking::{
+    offence::{OffenceDetails, OffenceSeverity, OnOffenceHandler},
+    SessionIndex,
+};
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+// The call enum expanded by `#[pallet::call]` trips these lints on the pinned toolchain.
+#[allow(unreachable_patterns, clippy::multiple_bound_locations)]
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching runtime event type.
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        /// The origin which may add and remove validators.
+        type ValidatorOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Minimum number of validators, below which `remove_validator` fails.
+        type MinValidators: Get<u32>;
+        /// Maximum number of validators.
+        type MaxValidators: Get<u32>;
+        /// Tells whether an account has session keys, usually `pallet_session`.
+        type ValidatorRegistration: ValidatorRegistration<Self::AccountId>;
+        /// A type representing the weights required by the dispatchables of this pallet.
+        type WeightInfo: WeightInfo;
+    }
+
+    /// The validators of the next planned session.
+    ///
+    /// Changes are handed to `pallet_session` when it plans a new session, so they take effect
+    /// one full session later, once the validators had the time to set their keys.
+    #[pallet::storage]
+    pub type Validators<T: Config> =
+        StorageValue<_, BoundedVec<T::AccountId, T::MaxValidators>, ValueQuery>;
+
+    /// Whether `Validators` must be handed to `pallet_session` when it plans the next session.
+    ///
+    /// Set when the set changes or an offender is disabled, and cleared once every planned
+    /// validator has session keys.
+    #[pallet::storage]
+    pub type ValidatorsChanged<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Initial validators. They need session keys in the `pallet_session` genesis config.
+        pub validators: Vec<T::AccountId>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            let validators: BoundedVec<_, T::MaxValidators> = self
+                .validators
+                .clone()
+                .try_into()
+                .expect("the genesis validators are more than `MaxValidators`");
+            Validators::<T>::put(validators);
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// The validator joins the set at the next session boundary but one.
+        ValidatorAdded { who: T::AccountId },
+        /// The validator leaves the set at the next session boundary but one.
+        ValidatorRemoved { who: T::AccountId },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The account is already a validator.
+        AlreadyValidator,
+        /// The account is not a validator.
+        NotValidator,
+        /// The account has no session keys.
+        NoSessionKeys,
+        /// The set already has `MaxValidators` validators.
+        TooManyValidators,
+        /// The set would drop below `MinValidators` validators.
+        TooFewValidators,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Adds a validator to the set.
+        ///
+        /// The validator must have set its session keys with `Session::set_keys`.
+        ///
+        /// ## Arguments:
+        /// - `origin`: Must be `ValidatorOrigin`.
+        /// - `who`: The account of the new validator.
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::add_validator())]
+        pub fn add_validator(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::ValidatorOrigin::ensure_origin(origin)?;
+            ensure!(
+                T::ValidatorRegistration::is_registered(&who),
+                Error::<T>::NoSessionKeys
+            );
+            Validators::<T>::try_mutate(|validators| {
+                ensure!(!validators.contains(&who), Error::<T>::AlreadyValidator);
+                validators
+                    .try_push(who.clone())
+                    .map_err(|_| Error::<T>::TooManyValidators)?;
+                Ok::<_, Error<T>>(())
+            })?;
+            ValidatorsChanged::<T>::put(true);
+            Self::deposit_event(Event::ValidatorAdded { who });
+            Ok(())
+        }
+
+        /// Removes a validator from the set.
+        ///
+        /// ## Arguments:
+        /// - `origin`: Must be `ValidatorOrigin`.
+        /// - `who`: The account of the validator to remove.
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::remove_validator())]
+        pub fn remove_validator(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::ValidatorOrigin::ensure_origin(origin)?;
+            Validators::<T>::try_mutate(|validators| {
+                let position = validators
+                    .iter()
+                    .position(|validator| validator == &who)
+                    .ok_or(Error::<T>::NotValidator)?;
+                ensure!(
+                    validators.len() as u32 > T::MinValidators::get(),
+                    Error::<T>::TooFewValidators
+                );
+                validators.remove(position);
+                Ok::<_, Error<T>>(())
+            })?;
+            ValidatorsChanged::<T>::put(true);
+            Self::deposit_event(Event::ValidatorRemoved { who });
+            Ok(())
+        }
+    }
+}
+
+/// Plans `Validators` only when they changed, so that `pallet_session` keeps the current set
+/// otherwise.
+///
+/// `pallet_session` skips the validators without session keys, for instance after they purged
+/// them, so a changed set keeps being planned until all of its validators have keys. A set with
+/// fewer than `MinValidators` validators with keys is not planned at all, as it would leave Aura
+/// and GRANDPA without enough authorities.
+impl<T> pallet_session::SessionManager<T::AccountId> for Pallet<T>
+where
+    T: Config + pallet_session::Config<ValidatorId = <T as frame_system::Config>::AccountId>,
+{
+    fn new_session(_new_index: u32) -> Option<Vec<T::AccountId>> {
+        if !ValidatorsChanged::<T>::get() {
+            return None;
+        }
+        let validators = Validators::<T>::get().into_inner();
+        let registered = validators
+            .iter()
+            .filter(|who| T::ValidatorRegistration::is_registered(who))
+            .count() as u32;
+        if registered < T::MinValidators::get().max(1) {
+            return None;
+        }
+        if registered == validators.len() as u32 {
+            ValidatorsChanged::<T>::kill();
+        }
+        Some(validators)
+    }
+
+    fn new_session_genesis(_new_index: u32) -> Option<Vec<T::AccountId>> {
+        Some(Validators::<T>::get().into_inner())
+    }
+
+    fn end_session(_end_index: u32) {}
+
+    fn start_session(_start_index: u32) {}
+}
+
+/// Validators carry no stake, so their full identification in `pallet_session::historical` is
+/// the unit type.
+impl<T> pallet_session::historical::SessionManager<T::AccountId, ()> for Pallet<T>
+where
+    T: Config + pallet_session::Config<ValidatorId = <T as frame_system::Config>::AccountId>,
+{
+    fn new_session(new_index: u32) -> Option<Vec<(T::AccountId, ())>> {
+        <Self as pallet_session::SessionManager<_>>::new_session(new_index)
+            .map(|validators| validators.into_iter().map(|who| (who, ())).collect())
+    }
+
+    fn new_session_genesis(new_index: u32) -> Option<Vec<(T::AccountId, ())>> {
+        <Self as pallet_session::SessionManager<_>>::new_session_genesis(new_index)
+            .map(|validators| validators.into_iter().map(|who| (who, ())).collect())
+    }
+
+    fn end_session(_end_index: u32) {}
+
+    fn start_session(_start_index: u32) {}
+}
+
+/// Converts a validator into its unit full identification, see the historical `SessionManager`.
+pub struct UnitIdentificationOf;
+impl<ValidatorId> Convert<ValidatorId, Option<()>> for UnitIdentificationOf {
+    fn convert(_: ValidatorId) -> Option<()> {
+        Some(())
+    }
+}
+
+/// Reports the offenders to `pallet_session`, whose `DisablingStrategy` decides which of them
+/// are disabled. The set is then planned again, so they stay disabled until the session after
+/// next, when `pallet_session` applies it.
+///
+/// Nothing is slashed, the slash fraction only sets the severity of the offence.
+impl<T, FullIdentification>
+    OnOffenceHandler<T::AccountId, (T::AccountId, FullIdentification), Weight> for Pallet<T>
+where
+    T: Config + pallet_session::Config<ValidatorId = <T as frame_system::Config>::AccountId>,
+{
+    fn on_offence(
+        offenders: &[OffenceDetails<T::AccountId, (T::AccountId, FullIdentification)>],
+        slash_fraction: &[Perbill],
+        _session: SessionIndex,
+    ) -> Weight {
+        for (details, fraction) in offenders.iter().zip(slash_fraction) {
+            pallet_session::Pallet::<T>::report_offence(
+                details.offender.0.clone(),
+                OffenceSeverity(*fraction),
+            );
+        }
+        if !offenders.is_empty() {
+            ValidatorsChanged::<T>::put(true);
+        }
+        T::DbWeight::get()
+            .reads_writes(2, 1)
+            .saturating_mul(offenders.len() as u64)
+            .saturating_add(T::DbWeight::get().writes(1))
+    }
+}
@@ -0,0 +1,126 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+use crate as pallet;
+use codec::Encode;
+use frame_support::{
+    derive_impl,
+    traits::{ConstU32, ConstU64},
+};
+use frame_system::EnsureRoot;
+use pallet_session::{disabling::UpToLimitDisablingStrategy, PeriodicSessions, TestSessionHandler};
+use sp_runtime::{testing::UintAuthorityId, traits::ConvertInto, BuildStorage};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+#[frame_support::runtime]
+mod runtime {
+    // The main runtime
+    #[runtime::runtime]
+    // Runtime Types to be generated
+    #[runtime::derive(
+        RuntimeCall,
+        RuntimeEvent,
+        RuntimeError,
+        RuntimeOrigin,
+        RuntimeFreezeReason,
+        RuntimeHoldReason,
+        RuntimeSlashReason,
+        RuntimeLockId,
+        RuntimeTask
+    )]
+    pub struct Test;
+
+    #[runtime::pallet_index(0)]
+    pub type System = frame_system::Pallet<Test>;
+
+    #[runtime::pallet_index(1)]
+    pub type ValidatorSet = pallet::Pallet<Test>;
+
+    #[runtime::pallet_index(2)]
+    pub type Session = pallet_session::Pallet<Test>;
+}
+
+/// Length of a session, in blocks.
+pub const PERIOD: u64 = 5;
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+    type Block = Block;
+}
+
+impl pallet_session::Config for Test {
+    /// Up to two of the three genesis validators can be disabled.
+    type DisablingStrategy = UpToLimitDisablingStrategy<1>;
+    type Keys = UintAuthorityId;
+    type NextSessionRotation = PeriodicSessions<ConstU64<PERIOD>, ConstU64<0>>;
+    type RuntimeEvent = RuntimeEvent;
+    type SessionHandler = TestSessionHandler;
+    type SessionManager = ValidatorSet;
+    type ShouldEndSession = PeriodicSessions<ConstU64<PERIOD>, ConstU64<0>>;
+    type ValidatorId = u64;
+    type ValidatorIdOf = ConvertInto;
+    type WeightInfo = ();
+}
+
+impl pallet::Config for Test {
+    type MaxValidators = ConstU32<4>;
+    type MinValidators = ConstU32<2>;
+    type RuntimeEvent = RuntimeEvent;
+    type ValidatorOrigin = EnsureRoot<Self::AccountId>;
+    type ValidatorRegistration = Session;
+    type WeightInfo = ();
+}
+
+// Build genesis storage according to the mock runtime: validators 1, 2 and 3, and session keys
+// for accounts 1 to 5.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap();
+    pallet::GenesisConfig::<Test> {
+        validators: vec![1, 2, 3],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    // Accounts with session keys exist, so that purging their keys releases a consumer.
+    for who in 1..=5 {
+        let account = frame_system::AccountInfo::<u32, ()> {
+            providers: 1,
+            ..Default::default()
+        };
+        storage.top.insert(
+            frame_system::Account::<Test>::hashed_key_for(who),
+            account.encode(),
+        );
+    }
+    pallet_session::GenesisConfig::<Test> {
+        keys: (1..=5).map(|i| (i, i, UintAuthorityId(i))).collect(),
+        ..Default::default()
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    storage.into()
+}
@@ -0,0 +1,257 @@
+// GNU General Public License (GPL)
+// Version 3, 29 June 2007
+// http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Copyright 2024 Benjamin Gallois
+//
+// Licensed under the GNU General Public License, Version 3 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.gnu.org/licenses/gpl-3.0.html
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// You may not distribute modified versions of the software without providing
+// the source code, and any derivative works must be licensed under the GPL
+// License as well. This ensures that the software remains free and open
+// for all users.
+//
+// You should have received a copy of the GPL along with this program.
+// If not, see <http://www.gnu.org/licenses/>.
+use crate::{mock::*, Error, Event, Validators, ValidatorsChanged};
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use frame_system::RawOrigin;
+use pallet_session::historical::SessionManager;
+use sp_runtime::{testing::UintAuthorityId, DispatchError, Perbill};
+use sp_staking::offence::{OffenceDetails, OnOffenceHandler};
+
+/// Reports `offender` to the pallet as the offence handler.
+fn report(offender: u64) {
+    let _ = <ValidatorSet as OnOffenceHandler<u64, (u64, ()), _>>::on_offence(
+        &[OffenceDetails {
+            offender: (offender, ()),
+            reporters: vec![],
+        }],
+        &[Perbill::one()],
+        Session::current_index(),
+    );
+}
+
+/// Runs the session hooks up to block `n`.
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        let next = System::block_number() + 1;
+        System::set_block_number(next);
+        Session::on_initialize(next);
+    }
+}
+
+#[test]
+fn genesis_config_works() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Validators::<Test>::get().into_inner(), vec![1, 2, 3]);
+        assert_eq!(Session::validators(), vec![1, 2, 3]);
+    })
+}
+
+#[test]
+fn add_validator_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            ValidatorSet::add_validator(RawOrigin::Signed(1).into(), 4),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            ValidatorSet::add_validator(RawOrigin::Root.into(), 1),
+            Error::<Test>::AlreadyValidator
+        );
+
+        assert_ok!(ValidatorSet::add_validator(RawOrigin::Root.into(), 4));
+        System::assert_last_event(Event::ValidatorAdded { who: 4 }.into());
+        assert_eq!(Validators::<Test>::get().into_inner(), vec![1, 2, 3, 4]);
+
+        assert_noop!(
+            ValidatorSet::add_validator(RawOrigin::Root.into(), 5),
+            Error::<Test>::TooManyValidators
+        );
+    })
+}
+
+#[test]
+fn remove_validator_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            ValidatorSet::remove_validator(RawOrigin::Signed(1).into(), 3),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            ValidatorSet::remove_validator(RawOrigin::Root.into(), 4),
+            Error::<Test>::NotValidator
+        );
+
+        assert_ok!(ValidatorSet::remove_validator(RawOrigin::Root.into(), 2));
+        System::assert_last_event(Event::ValidatorRemoved { who: 2 }.into());
+        assert_eq!(Validators::<Test>::get().into_inner(), vec![1, 3]);
+
+        assert_noop!(
+            ValidatorSet::remove_validator(RawOrigin::Root.into(), 1),
+            Error::<Test>::TooFewValidators
+        );
+    })
+}
+
+#[test]
+fn changes_take_effect_at_session_boundaries() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorSet::add_validator(RawOrigin::Root.into(), 4));
+        assert_ok!(ValidatorSet::remove_validator(RawOrigin::Root.into(), 1));
+
+        // The change is planned at the first boundary...
+        run_to_block(PERIOD);
+        assert_eq!(Session::current_index(), 1);
+        assert_eq!(Session::validators(), vec![1, 2, 3]);
+        assert_eq!(
+            Session::queued_keys()
+                .iter()
+                .map(|(who, _)| *who)
+                .collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+
+        // ...and enacted at the second one.
+        run_to_block(2 * PERIOD);
+        assert_eq!(Session::current_index(), 2);
+        assert_eq!(Session::validators(), vec![2, 3, 4]);
+    })
+}
+
+#[test]
+fn validators_need_session_keys() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ValidatorSet::add_validator(RawOrigin::Root.into(), 6),
+            Error::<Test>::NoSessionKeys
+        );
+
+        // The validator sets its keys, and joins at the second session boundary.
+        System::inc_providers(&6);
+        assert_ok!(Session::set_keys(
+            RawOrigin::Signed(6).into(),
+            UintAuthorityId(6),
+            vec![]
+        ));
+        assert_ok!(ValidatorSet::add_validator(RawOrigin::Root.into(), 6));
+        run_to_block(PERIOD);
+        assert_eq!(Session::validators(), vec![1, 2, 3]);
+        run_to_block(2 * PERIOD);
+        assert_eq!(Session::validators(), vec![1, 2, 3, 6]);
+    })
+}
+
+#[test]
+fn sets_without_enough_keys_are_not_planned() {
+    new_test_ext().execute_with(|| {
+        // Only validator 1 keeps its keys, and it is removed.
+        assert_ok!(Session::purge_keys(RawOrigin::Signed(2).into()));
+        assert_ok!(Session::purge_keys(RawOrigin::Signed(3).into()));
+        assert_ok!(ValidatorSet::remove_validator(RawOrigin::Root.into(), 1));
+        assert_eq!(
+            <ValidatorSet as SessionManager<u64, ()>>::new_session(1),
+            None
+        );
+        assert!(ValidatorsChanged::<Test>::get());
+
+        // The validator with keys keeps producing blocks.
+        run_to_block(3 * PERIOD);
+        assert_eq!(Session::validators(), vec![1]);
+
+        // The set is planned once enough of its validators have keys again.
+        assert_ok!(Session::set_keys(
+            RawOrigin::Signed(2).into(),
+            UintAuthorityId(2),
+            vec![]
+        ));
+        assert_ok!(Session::set_keys(
+            RawOrigin::Signed(3).into(),
+            UintAuthorityId(3),
+            vec![]
+        ));
+        run_to_block(5 * PERIOD);
+        assert_eq!(Session::validators(), vec![2, 3]);
+        assert!(!ValidatorsChanged::<Test>::get());
+    })
+}
+
+#[test]
+fn only_changed_sets_are_planned() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            <ValidatorSet as SessionManager<u64, ()>>::new_session_genesis(0),
+            Some(vec![(1, ()), (2, ()), (3, ())])
+        );
+        assert_eq!(
+            <ValidatorSet as SessionManager<u64, ()>>::new_session(1),
+            None
+        );
+
+        assert_ok!(ValidatorSet::add_validator(RawOrigin::Root.into(), 4));
+        assert!(ValidatorsChanged::<Test>::get());
+        assert_eq!(
+            <ValidatorSet as SessionManager<u64, ()>>::new_session(2),
+            Some(vec![(1, ()), (2, ()), (3, ()), (4, ())])
+        );
+        assert!(!ValidatorsChanged::<Test>::get());
+        assert_eq!(
+            <ValidatorSet as SessionManager<u64, ()>>::new_session(3),
+            None
+        );
+
+        // A validator without session keys keeps the set planned until it sets them again.
+        assert_ok!(Session::purge_keys(RawOrigin::Signed(3).into()));
+        assert_ok!(ValidatorSet::remove_validator(RawOrigin::Root.into(), 4));
+        assert!(<ValidatorSet as SessionManager<u64, ()>>::new_session(4).is_some());
+        assert!(<ValidatorSet as SessionManager<u64, ()>>::new_session(5).is_some());
+        assert_ok!(Session::set_keys(
+            RawOrigin::Signed(3).into(),
+            UintAuthorityId(3),
+            vec![]
+        ));
+        assert!(<ValidatorSet as SessionManager<u64, ()>>::new_session(6).is_some());
+        assert_eq!(
+            <ValidatorSet as SessionManager<u64, ()>>::new_session(7),
+            None
+        );
+    })
+}
+
+#[test]
+fn offenders_are_disabled_until_a_new_set_is_applied() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        report(2);
+        assert_eq!(Session::disabled_validators(), vec![1]);
+
+        // Reporting the same offender again changes nothing.
+        report(2);
+        assert_eq!(Session::disabled_validators(), vec![1]);
+
+        // The disabling strategy caps the number of disabled validators.
+        report(3);
+        report(1);
+        assert_eq!(Session::disabled_validators(), vec![1, 2]);
+
+        // Offenders stay in the set and are enabled again when a new set is applied.
+        run_to_block(PERIOD);
+        assert_eq!(Session::disabled_validators(), vec![1, 2]);
+        run_to_block(2 * PERIOD);
+        assert!(Session::disabled_validators().is_empty());
+        assert_eq!(Session::validators(), vec![1, 2, 3]);
+    })
+}
@@ -28,9 +28,13 @@ frame_benchmarking::define_benchmarks!(
     [frame_system, SystemBench::<Runtime>]
     [frame_system_extensions, SystemExtensionsBench::<Runtime>]
     [pallet_balances, Balances]
+    [pallet_block_reward, BlockReward]
+    [pallet_collective, Council]
+    [pallet_motion, Motion]
     [pallet_timestamp, Timestamp]
     [pallet_sudo, Sudo]
     [pallet_utility, Utility]
+    [pallet_validator_set, ValidatorSet]
     [pallet_feeless, Account]
     [pallet_template, Template]
 );
@@ -24,27 +24,42 @@
 // For more information, please refer to <http://unlicense.org>
 
 // Substrate and Polkadot dependencies
+use alloc::{vec, vec::Vec};
+use codec::{Compact, MaxEncodedLen};
+use core::marker::PhantomData;
 use frame_support::{
     derive_impl,
-    pallet_prelude::Zero,
+    dispatch::DispatchClass,
+    pallet_prelude::{OptionQuery, Zero},
     parameter_types,
-    traits::{ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, VariantCountOf},
+    traits::{
+        ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, EitherOfDiverse, Get, VariantCountOf,
+    },
     weights::{
         constants::{RocksDbWeight, WEIGHT_REF_TIME_PER_SECOND},
-        Weight,
+        ConstantMultiplier, Weight, WeightToFee,
     },
+    PalletId,
+};
+use frame_system::{
+    limits::{BlockLength, BlockWeights},
+    EnsureRoot,
 };
-use frame_system::limits::{BlockLength, BlockWeights};
-use pallet_transaction_payment::{ConstFeeMultiplier, FungibleAdapter, Multiplier};
+use pallet_transaction_payment::{FungibleAdapter, Multiplier, MultiplierUpdate};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_runtime::Perbill;
+use sp_runtime::{
+    traits::{AccountIdConversion, Convert, ConvertInto, OpaqueKeys, Saturating, Verify},
+    FixedPointNumber, Perbill, Perquintill,
+};
 use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-    Account, AccountId, Aura, Balance, Balances, Block, BlockNumber, Hash, Nonce, PalletInfo,
-    Runtime, RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin,
-    RuntimeTask, EXISTENTIAL_DEPOSIT, SLOT_DURATION, VERSION,
+    Account, AccountId, Aura, Balance, Balances, Block, BlockNumber, Hash, Historical, Nonce,
+    Offences, PalletInfo, Runtime, RuntimeCall, RuntimeEvent, RuntimeFreezeReason,
+    RuntimeHoldReason, RuntimeOrigin, RuntimeTask, Session, SessionKeys, Signature, Sudo,
+    UncheckedExtrinsic, ValidatorSet, DAYS, EXISTENTIAL_DEPOSIT, MICRO_UNIT, MINUTES,
+    SLOT_DURATION, VERSION,
 };
 
 const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
@@ -95,21 +110,91 @@ impl frame_system::Config for Runtime {
 impl pallet_aura::Config for Runtime {
     type AllowMultipleBlocksPerSlot = ConstBool<false>;
     type AuthorityId = AuraId;
-    type DisabledValidators = ();
+    type DisabledValidators = Session;
     type MaxAuthorities = ConstU32<32>;
     type SlotDuration = pallet_aura::MinimumPeriodTimesTwo<Runtime>;
 }
 
+parameter_types! {
+    /// How long, in blocks, an equivocation stays reportable.
+    pub const ReportLongevity: u64 = DAYS as u64;
+    /// The set id to session mappings kept to check equivocation reports, one day of sessions.
+    pub const MaxSetIdSessionEntries: u64 = (DAYS / SessionPeriod::get()) as u64;
+}
+
 impl pallet_grandpa::Config for Runtime {
-    type EquivocationReportSystem = ();
-    type KeyOwnerProof = sp_core::Void;
+    type EquivocationReportSystem =
+        pallet_grandpa::EquivocationReportSystem<Self, Offences, Historical, ReportLongevity>;
+    type KeyOwnerProof = sp_session::MembershipProof;
     type MaxAuthorities = ConstU32<32>;
     type MaxNominators = ConstU32<0>;
-    type MaxSetIdSessionEntries = ConstU64<0>;
+    type MaxSetIdSessionEntries = MaxSetIdSessionEntries;
     type RuntimeEvent = RuntimeEvent;
     type WeightInfo = ();
 }
 
+impl pallet_authorship::Config for Runtime {
+    type EventHandler = ();
+    type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
+}
+
+impl pallet_offences::Config for Runtime {
+    type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+    type OnOffenceHandler = ValidatorSet;
+    type RuntimeEvent = RuntimeEvent;
+}
+
+impl<LocalCall> frame_system::offchain::CreateTransactionBase<LocalCall> for Runtime
+where
+    RuntimeCall: From<LocalCall>,
+{
+    type Extrinsic = UncheckedExtrinsic;
+    type RuntimeCall = RuntimeCall;
+}
+
+/// Used by GRANDPA to submit unsigned equivocation reports from the node.
+impl<LocalCall> frame_system::offchain::CreateInherent<LocalCall> for Runtime
+where
+    RuntimeCall: From<LocalCall>,
+{
+    fn create_inherent(call: RuntimeCall) -> UncheckedExtrinsic {
+        UncheckedExtrinsic::new_bare(call)
+    }
+}
+
+parameter_types! {
+    pub const SessionPeriod: BlockNumber = 10 * MINUTES;
+    pub const SessionOffset: BlockNumber = 0;
+}
+
+impl pallet_session::Config for Runtime {
+    /// Disables offenders, up to a third of the validators.
+    type DisablingStrategy = pallet_session::disabling::UpToLimitDisablingStrategy;
+    type Keys = SessionKeys;
+    type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+    type RuntimeEvent = RuntimeEvent;
+    type SessionHandler = <SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+    type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, ValidatorSet>;
+    type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+    type ValidatorId = AccountId;
+    type ValidatorIdOf = ConvertInto;
+    type WeightInfo = pallet_session::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_session::historical::Config for Runtime {
+    type FullIdentification = ();
+    type FullIdentificationOf = pallet_validator_set::UnitIdentificationOf;
+}
+
+impl pallet_validator_set::Config for Runtime {
+    type MaxValidators = ConstU32<32>;
+    type MinValidators = ConstU32<1>;
+    type RuntimeEvent = RuntimeEvent;
+    type ValidatorOrigin = EnsureRootOrHalfCouncil;
+    type ValidatorRegistration = Session;
+    type WeightInfo = pallet_validator_set::weights::SubstrateWeight<Runtime>;
+}
+
 impl pallet_timestamp::Config for Runtime {
     type MinimumPeriod = ConstU64<{ SLOT_DURATION / 2 }>;
     /// A timestamp: milliseconds since the unix epoch.
@@ -118,13 +203,192 @@ impl pallet_timestamp::Config for Runtime {
     type WeightInfo = ();
 }
 
+mod sudo {
+    use super::*;
+
+    /// The sudo key, which `pallet_sudo` keeps private.
+    #[frame_support::storage_alias(pallet_name)]
+    pub type Key = StorageValue<Sudo, AccountId, OptionQuery>;
+}
+
+/// Signers a call of `FeelessExemptCalls` is restricted to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExemptSigner {
+    /// The sudo key.
+    SudoKey,
+    /// A member of the council.
+    CouncilMember,
+}
+
+/// Calls that stay available to throttled accounts, when signed by the accounts allowed to
+/// dispatch them.
+///
+/// These are sudo calls of the sudo key, so that it can always act in an emergency, council
+/// votes and closes of council members, and non-empty utility batches made only of such calls.
+///
+/// These calls are rate limited as usual for other signers, who could otherwise fill blocks for
+/// free with calls failing at dispatch.
+pub struct FeelessExemptCalls;
+impl FeelessExemptCalls {
+    /// Returns whether `call` is exempt for a signer that is each of the `ExemptSigner`s for
+    /// which `is_signer` returns `true`.
+    ///
+    /// This does not access the storage, so that `|_| true` tells, outside of the runtime,
+    /// whether `call` may be exempt.
+    pub fn matches(call: &RuntimeCall, is_signer: &impl Fn(ExemptSigner) -> bool) -> bool {
+        match call {
+            RuntimeCall::Sudo(_) => is_signer(ExemptSigner::SudoKey),
+            RuntimeCall::Council(
+                pallet_collective::Call::vote { .. } | pallet_collective::Call::close { .. },
+            ) => is_signer(ExemptSigner::CouncilMember),
+            RuntimeCall::Utility(
+                pallet_utility::Call::batch { calls }
+                | pallet_utility::Call::batch_all { calls }
+                | pallet_utility::Call::force_batch { calls },
+            ) => !calls.is_empty() && calls.iter().all(|call| Self::matches(call, is_signer)),
+            _ => false,
+        }
+    }
+}
+impl pallet_feeless::IsExempt<AccountId, RuntimeCall> for FeelessExemptCalls {
+    fn is_exempt(who: &AccountId, call: &RuntimeCall) -> bool {
+        Self::matches(call, &|signer| match signer {
+            ExemptSigner::SudoKey => sudo::Key::get().as_ref() == Some(who),
+            ExemptSigner::CouncilMember => {
+                pallet_collective::Pallet::<Runtime, CouncilCollective>::is_member(who)
+            }
+        })
+    }
+
+    /// Reads the sudo key and the council members at most once each, however many calls a batch
+    /// nests, as repeated reads are served from the overlay.
+    fn weight(call: &RuntimeCall) -> Weight {
+        let read = core::cell::Cell::new((false, false));
+        Self::matches(call, &|signer| {
+            let (sudo, council) = read.get();
+            read.set(match signer {
+                ExemptSigner::SudoKey => (true, council),
+                ExemptSigner::CouncilMember => (sudo, true),
+            });
+            true
+        });
+        let (sudo, council) = read.get();
+        let mut weight = Weight::zero();
+        if sudo {
+            weight.saturating_accrue(
+                RocksDbWeight::get()
+                    .reads(1)
+                    .saturating_add(Weight::from_parts(0, AccountId::max_encoded_len() as u64)),
+            );
+        }
+        if council {
+            let members = CouncilMaxMembers::get() as u64 * AccountId::max_encoded_len() as u64
+                + Compact::<u32>::max_encoded_len() as u64;
+            weight.saturating_accrue(
+                RocksDbWeight::get()
+                    .reads(1)
+                    .saturating_add(Weight::from_parts(0, members)),
+            );
+        }
+        weight
+    }
+}
+
+/// Signs attestations with a well-known sr25519 issuer key in the benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct FeelessBenchmarkHelper;
+#[cfg(feature = "runtime-benchmarks")]
+impl FeelessBenchmarkHelper {
+    fn key() -> AuraId {
+        use sp_runtime::RuntimeAppPublic;
+        AuraId::generate_pair(Some(b"//Issuer".to_vec()))
+    }
+}
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_feeless::BenchmarkHelper<AccountId, Signature> for FeelessBenchmarkHelper {
+    fn issuer() -> AccountId {
+        sp_core::sr25519::Public::from(Self::key()).into()
+    }
+
+    fn sign(message: &[u8]) -> Signature {
+        use sp_runtime::RuntimeAppPublic;
+        let signature = Self::key()
+            .sign(&message)
+            .expect("the key was generated in the keystore; qed");
+        sp_core::sr25519::Signature::from(signature).into()
+    }
+}
+
+/// Resolves the accounts `utility::as_derivative` calls are dispatched as, including inside
+/// batches, so that `CheckRate` can charge them.
+pub struct FeelessEffectiveAccounts;
+impl pallet_feeless::EffectiveAccounts<AccountId, RuntimeCall> for FeelessEffectiveAccounts {
+    fn effective_accounts(who: &AccountId, call: &RuntimeCall) -> Vec<AccountId> {
+        match call {
+            RuntimeCall::Utility(pallet_utility::Call::as_derivative { index, call }) => {
+                let derivative =
+                    pallet_utility::Pallet::<Runtime>::derivative_account_id(who.clone(), *index);
+                let nested = Self::effective_accounts(&derivative, call);
+                if nested.is_empty() {
+                    vec![derivative]
+                } else {
+                    nested
+                }
+            }
+            RuntimeCall::Utility(
+                pallet_utility::Call::batch { calls }
+                | pallet_utility::Call::batch_all { calls }
+                | pallet_utility::Call::force_batch { calls },
+            ) => calls
+                .iter()
+                .flat_map(|call| Self::effective_accounts(who, call))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+parameter_types! {
+    /// Charge both the signer and derivative accounts, so that derivatives do not multiply the
+    /// quota of an account.
+    pub const FeelessDerivativeCharge: pallet_feeless::DerivativeCharge =
+        pallet_feeless::DerivativeCharge::Both;
+}
+
 impl pallet_feeless::Config for Runtime {
+    type AttestationSignature = Signature;
+    type AttestationSigner = <Signature as Verify>::Signer;
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = FeelessBenchmarkHelper;
+    type DerivativeCharge = FeelessDerivativeCharge;
+    type EffectiveAccounts = FeelessEffectiveAccounts;
+    type ExemptCalls = FeelessExemptCalls;
+    type MaxReputationRules = ConstU32<8>;
+    type MaxSingleTxSize = ConstU32<128>;
     type MaxSizeByPeriod = ConstU32<128>;
+    type MaxStatusBatch = ConstU32<512>;
     type MaxTxByPeriod = ConstU32<1>;
     type Period = ConstU32<5>;
     type RuntimeEvent = RuntimeEvent;
-    type StatusOrigin = frame_system::EnsureRoot<AccountId>;
-    type WeightInfo = ();
+    type StatusOrigin = EnsureRootOrHalfCouncil;
+    type WeightInfo = pallet_feeless::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+    /// The account receiving the treasury cut of block rewards.
+    pub TreasuryAccount: AccountId = TreasuryPalletId::get().into_account_truncating();
+}
+
+impl pallet_block_reward::Config for Runtime {
+    type Balance = Balance;
+    type Currency = Balances;
+    type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
+    type MaxScheduleSteps = ConstU32<16>;
+    type RewardOrigin = EnsureRootOrHalfCouncil;
+    type RuntimeEvent = RuntimeEvent;
+    type TreasuryAccount = TreasuryAccount;
+    type WeightInfo = pallet_block_reward::weights::SubstrateWeight<Runtime>;
 }
 
 impl pallet_balances::Config for Runtime {
@@ -147,17 +411,103 @@ impl pallet_balances::Config for Runtime {
 }
 
 parameter_types! {
-    pub FeeMultiplier: Multiplier = Multiplier::zero();
+    /// Switch to fees when the chain is congested. When `false`, the chain is fully feeless.
+    pub const HybridFees: bool = true;
+    /// Block fullness above which fees are charged.
+    pub const CongestionThreshold: Perquintill = Perquintill::from_percent(50);
+    /// Upper bound of the fee multiplier under sustained congestion.
+    pub MaxFeeMultiplier: Multiplier = Multiplier::from_u32(1_024);
+}
+
+/// Keeps the fee multiplier at zero while the chain is not congested.
+///
+/// A block is congested when its fullness, by weight or by length of the normal class, is above
+/// [`CongestionThreshold`]. The multiplier then starts at one and doubles every congested block
+/// up to [`MaxFeeMultiplier`], and falls back to zero as soon as a block is below the threshold.
+/// The multiplier stays at zero if `H`, e.g. [`HybridFees`], is `false`.
+pub struct CongestionMultiplier<H>(PhantomData<H>);
+impl<H> CongestionMultiplier<H> {
+    fn fullness() -> Perquintill {
+        let weights = RuntimeBlockWeights::get();
+        let max_weight = weights
+            .get(DispatchClass::Normal)
+            .max_total
+            .unwrap_or(weights.max_block);
+        let weight = frame_system::BlockWeight::<Runtime>::get();
+        let max_length = *RuntimeBlockLength::get().max.get(DispatchClass::Normal);
+        let length = frame_system::AllExtrinsicsLen::<Runtime>::get().unwrap_or_default();
+        Perquintill::from_rational(
+            weight.get(DispatchClass::Normal).ref_time(),
+            max_weight.ref_time(),
+        )
+        .max(Perquintill::from_rational(
+            u64::from(length),
+            u64::from(max_length),
+        ))
+    }
+}
+impl<H: Get<bool>> Convert<Multiplier, Multiplier> for CongestionMultiplier<H> {
+    fn convert(previous: Multiplier) -> Multiplier {
+        if !H::get() || Self::fullness() <= CongestionThreshold::get() {
+            return Multiplier::zero();
+        }
+        previous
+            .max(Multiplier::saturating_from_rational(1, 2))
+            .saturating_mul(Self::variability())
+            .min(Self::max())
+    }
+}
+impl<H: Get<bool>> MultiplierUpdate for CongestionMultiplier<H> {
+    fn min() -> Multiplier {
+        Multiplier::zero()
+    }
+
+    fn max() -> Multiplier {
+        MaxFeeMultiplier::get()
+    }
+
+    fn target() -> Perquintill {
+        if H::get() {
+            CongestionThreshold::get()
+        } else {
+            Perquintill::zero()
+        }
+    }
+
+    fn variability() -> Multiplier {
+        Multiplier::from_u32(2)
+    }
+}
+
+/// Charges `F` while the chain is congested, that is while the fee multiplier is not zero, and
+/// nothing otherwise, so that accounts within their `CheckRate` quota transact for free under
+/// normal load.
+pub struct CongestionFee<F>(PhantomData<F>);
+impl<F: WeightToFee<Balance = Balance>> WeightToFee for CongestionFee<F> {
+    type Balance = Balance;
+
+    fn weight_to_fee(weight: &Weight) -> Balance {
+        // Not weighed, like the read of `NextFeeMultiplier` that `pallet_transaction_payment`
+        // does itself to apply the multiplier to the same fee: the value is small and, once
+        // read from the database in a block, served from the storage overlay.
+        if pallet_transaction_payment::NextFeeMultiplier::<Runtime>::get().is_zero() {
+            Zero::zero()
+        } else {
+            F::weight_to_fee(weight)
+        }
+    }
 }
 
 impl pallet_transaction_payment::Config for Runtime {
-    type FeeMultiplierUpdate = ConstFeeMultiplier<FeeMultiplier>;
-    type LengthToFee = frame_support::weights::FixedFee<0, Balance>;
+    type FeeMultiplierUpdate = CongestionMultiplier<HybridFees>;
+    /// One micro unit per byte, only under congestion.
+    type LengthToFee = CongestionFee<ConstantMultiplier<Balance, ConstU128<MICRO_UNIT>>>;
     type OnChargeTransaction = FungibleAdapter<Balances, ()>;
     type OperationalFeeMultiplier = ConstU8<0>;
     type RuntimeEvent = RuntimeEvent;
     type WeightInfo = pallet_transaction_payment::weights::SubstrateWeight<Runtime>;
-    type WeightToFee = frame_support::weights::FixedFee<0, Balance>;
+    /// About one milli unit for the base extrinsic weight, only under congestion.
+    type WeightToFee = CongestionFee<ConstantMultiplier<Balance, ConstU128<10>>>;
 }
 
 impl pallet_utility::Config for Runtime {
@@ -167,6 +517,48 @@ impl pallet_utility::Config for Runtime {
     type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
 }
 
+pub type CouncilCollective = pallet_collective::Instance1;
+
+/// Root, or more than half of the council. Used for the feeless, reward and validator set
+/// parameters, so that they no longer depend on the sudo key.
+pub type EnsureRootOrHalfCouncil = EitherOfDiverse<
+    EnsureRoot<AccountId>,
+    pallet_collective::EnsureProportionMoreThan<AccountId, CouncilCollective, 1, 2>,
+>;
+
+parameter_types! {
+    pub const CouncilMotionDuration: BlockNumber = DAYS;
+    pub const CouncilMaxProposals: u32 = 100;
+    pub const CouncilMaxMembers: u32 = 100;
+    pub MaxProposalWeight: Weight = Perbill::from_percent(50) * RuntimeBlockWeights::get().max_block;
+}
+
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+    type Consideration = ();
+    type DefaultVote = pallet_collective::PrimeDefaultVote;
+    type DisapproveOrigin = EnsureRoot<AccountId>;
+    type KillOrigin = EnsureRoot<AccountId>;
+    type MaxMembers = CouncilMaxMembers;
+    type MaxProposalWeight = MaxProposalWeight;
+    type MaxProposals = CouncilMaxProposals;
+    type MotionDuration = CouncilMotionDuration;
+    type Proposal = RuntimeCall;
+    type RuntimeEvent = RuntimeEvent;
+    type RuntimeOrigin = RuntimeOrigin;
+    type SetMembersOrigin = EnsureRoot<AccountId>;
+    type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+}
+
+/// Root calls, including runtime upgrades and council membership changes, need two thirds of
+/// the council once the sudo key is removed.
+impl pallet_motion::Config for Runtime {
+    type MotionOrigin =
+        pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 2, 3>;
+    type RuntimeCall = RuntimeCall;
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = pallet_motion::weights::SubstrateWeight<Runtime>;
+}
+
 impl pallet_sudo::Config for Runtime {
     type RuntimeCall = RuntimeCall;
     type RuntimeEvent = RuntimeEvent;
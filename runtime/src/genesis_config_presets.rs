@@ -15,21 +15,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{AccountId, BalancesConfig, RuntimeGenesisConfig, SudoConfig};
+use crate::{
+    AccountConfig, AccountId, BalancesConfig, BlockRewardConfig, CouncilConfig,
+    RuntimeGenesisConfig, SessionConfig, SessionKeys, SudoConfig, TransactionPaymentConfig,
+    ValidatorSetConfig, DAYS, UNIT,
+};
 use alloc::{vec, vec::Vec};
-use frame_support::build_struct_json_patch;
+use frame_support::{build_struct_json_patch, pallet_prelude::Zero};
+use pallet_block_reward::IssuanceStep;
+use pallet_transaction_payment::Multiplier;
 use serde_json::Value;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_consensus_grandpa::AuthorityId as GrandpaId;
 use sp_genesis_builder::{self, PresetId};
 use sp_keyring::Sr25519Keyring;
+use sp_runtime::Perbill;
 
 // Returns the genesis config presets populated with given parameters.
+//
+// Council members are `Unlimited` as well, so that they can always vote and close motions.
 fn testnet_genesis(
-    initial_authorities: Vec<(AuraId, GrandpaId)>,
+    initial_authorities: Vec<(AccountId, AuraId, GrandpaId)>,
     endowed_accounts: Vec<AccountId>,
+    mut unlimited_accounts: Vec<AccountId>,
+    council: Vec<AccountId>,
     root: AccountId,
 ) -> Value {
+    for member in &council {
+        if !unlimited_accounts.contains(member) {
+            unlimited_accounts.push(member.clone());
+        }
+    }
     build_struct_json_patch!(RuntimeGenesisConfig {
         balances: BalancesConfig {
             balances: endowed_accounts
@@ -38,19 +54,47 @@ fn testnet_genesis(
                 .map(|k| (k, 1u128 << 60))
                 .collect::<Vec<_>>(),
         },
-        aura: pallet_aura::GenesisConfig {
-            authorities: initial_authorities
+        // Aura and GRANDPA authorities are set by the session pallet from the validator set.
+        validator_set: ValidatorSetConfig {
+            validators: initial_authorities
                 .iter()
-                .map(|x| (x.0.clone()))
+                .map(|x| x.0.clone())
                 .collect::<Vec<_>>(),
         },
-        grandpa: pallet_grandpa::GenesisConfig {
-            authorities: initial_authorities
-                .iter()
-                .map(|x| (x.1.clone(), 1))
+        session: SessionConfig {
+            keys: initial_authorities
+                .into_iter()
+                .map(|(account, aura, grandpa)| {
+                    (account.clone(), account, SessionKeys { aura, grandpa })
+                })
                 .collect::<Vec<_>>(),
         },
+        council: CouncilConfig { members: council },
         sudo: SudoConfig { key: Some(root) },
+        // Start uncongested, hence feeless, rather than at the default multiplier of one.
+        transaction_payment: TransactionPaymentConfig {
+            multiplier: Multiplier::zero(),
+        },
+        // One unit per block, halved after a year, with a fifth of it going to the treasury.
+        block_reward: BlockRewardConfig {
+            schedule: vec![
+                IssuanceStep {
+                    from: 0,
+                    reward: UNIT,
+                },
+                IssuanceStep {
+                    from: 365 * DAYS,
+                    reward: UNIT / 2,
+                },
+            ],
+            treasury_cut: Perbill::from_percent(20),
+        },
+        account: AccountConfig {
+            statuses: unlimited_accounts
+                .into_iter()
+                .map(|k| (k, pallet_feeless::Status::Unlimited))
+                .collect::<Vec<_>>(),
+        },
     })
 }
 
@@ -58,6 +102,7 @@ fn testnet_genesis(
 pub fn development_config_genesis() -> Value {
     testnet_genesis(
         vec![(
+            Sr25519Keyring::Alice.to_account_id(),
             sp_keyring::Sr25519Keyring::Alice.public().into(),
             sp_keyring::Ed25519Keyring::Alice.public().into(),
         )],
@@ -67,6 +112,8 @@ pub fn development_config_genesis() -> Value {
             Sr25519Keyring::AliceStash.to_account_id(),
             Sr25519Keyring::BobStash.to_account_id(),
         ],
+        vec![Sr25519Keyring::Alice.to_account_id()],
+        vec![Sr25519Keyring::Alice.to_account_id()],
         sp_keyring::Sr25519Keyring::Alice.to_account_id(),
     )
 }
@@ -76,10 +123,12 @@ pub fn local_config_genesis() -> Value {
     testnet_genesis(
         vec![
             (
+                Sr25519Keyring::Alice.to_account_id(),
                 sp_keyring::Sr25519Keyring::Alice.public().into(),
                 sp_keyring::Ed25519Keyring::Alice.public().into(),
             ),
             (
+                Sr25519Keyring::Bob.to_account_id(),
                 sp_keyring::Sr25519Keyring::Bob.public().into(),
                 sp_keyring::Ed25519Keyring::Bob.public().into(),
             ),
@@ -88,6 +137,15 @@ pub fn local_config_genesis() -> Value {
             .filter(|v| v != &Sr25519Keyring::One && v != &Sr25519Keyring::Two)
             .map(|v| v.to_account_id())
             .collect::<Vec<_>>(),
+        vec![
+            Sr25519Keyring::Alice.to_account_id(),
+            Sr25519Keyring::Bob.to_account_id(),
+        ],
+        vec![
+            Sr25519Keyring::Alice.to_account_id(),
+            Sr25519Keyring::Bob.to_account_id(),
+            Sr25519Keyring::Charlie.to_account_id(),
+        ],
         Sr25519Keyring::Alice.to_account_id(),
     )
 }
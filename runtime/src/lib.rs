@@ -26,11 +26,14 @@ pub use pallet_timestamp::Call as TimestampCall;
 pub use sp_runtime::BuildStorage;
 
 pub mod genesis_config_presets;
+#[cfg(test)]
+mod tests;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
 /// of data like extrinsics, allowing for them to continue syncing the network through upgrades
 /// to even the core data structures.
+#[allow(clippy::too_long_first_doc_paragraph)]
 pub mod opaque {
     use super::*;
     use sp_runtime::{
@@ -69,10 +72,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     //   `spec_version`, and `authoring_version` are the same between Wasm and native.
     // This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
     //   the compatible custom types.
-    spec_version: 100,
+    spec_version: 101,
     impl_version: 1,
     apis: apis::RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
     system_version: 1,
 };
 
@@ -83,6 +86,7 @@ mod block_times {
     /// slot_duration()`.
     ///
     /// Change this to adjust the block time.
+    #[allow(clippy::too_long_first_doc_paragraph)]
     pub const MILLI_SECS_PER_BLOCK: u64 = 6000;
 
     // NOTE: Currently it is not possible to change the slot duration after the chain has started.
@@ -156,13 +160,18 @@ pub type TxExtension = (
     frame_system::CheckTxVersion<Runtime>,
     frame_system::CheckGenesis<Runtime>,
     frame_system::CheckEra<Runtime>,
-    frame_system::CheckNonce<Runtime>,
     pallet_feeless::CheckRate<Runtime>,
+    frame_system::CheckNonce<Runtime>,
     frame_system::CheckWeight<Runtime>,
     pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
     frame_metadata_hash_extension::CheckMetadataHash<Runtime>,
 );
 
+/// Returns the nonce of the proof-of-work stamp carried by the `CheckRate` of `tx_ext`, if any.
+pub fn check_rate_stamp(tx_ext: &TxExtension) -> Option<u64> {
+    tx_ext.5.stamp()
+}
+
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic =
     generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, TxExtension>;
@@ -233,4 +242,31 @@ mod runtime {
 
     #[runtime::pallet_index(9)]
     pub type Utility = pallet_utility;
+
+    #[runtime::pallet_index(10)]
+    pub type BlockReward = pallet_block_reward;
+
+    // Declared before `Session`, so that the block author is found in the validator set which
+    // authored the block, even when a new session starts in that block.
+    #[runtime::pallet_index(15)]
+    pub type Authorship = pallet_authorship;
+
+    // Declared before `Session`, whose genesis build reads the initial validators from it.
+    #[runtime::pallet_index(11)]
+    pub type ValidatorSet = pallet_validator_set;
+
+    #[runtime::pallet_index(12)]
+    pub type Session = pallet_session;
+
+    #[runtime::pallet_index(13)]
+    pub type Council = pallet_collective<Instance1>;
+
+    #[runtime::pallet_index(14)]
+    pub type Motion = pallet_motion;
+
+    #[runtime::pallet_index(16)]
+    pub type Offences = pallet_offences;
+
+    #[runtime::pallet_index(17)]
+    pub type Historical = pallet_session::historical;
 }
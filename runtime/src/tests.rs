@@ -0,0 +1,220 @@
+// This is free and unencumbered software released into the public domain.
+//
+// Anyone is free to copy, modify, publish, use, compile, sell, or
+// distribute this software, either in source code form or as a compiled
+// binary, for any purpose, commercial or non-commercial, and by any
+// means.
+//
+// In jurisdictions that recognize copyright laws, the author or authors
+// of this software dedicate any and all copyright interest in the
+// software to the public domain. We make this dedication for the benefit
+// of the public at large and to the detriment of our heirs and
+// successors. We intend this dedication to be an overt act of
+// relinquishment in perpetuity of all present and future rights to this
+// software under copyright law.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+// IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY CLAIM, DAMAGES OR
+// OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE,
+// ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR
+// OTHER DEALINGS IN THE SOFTWARE.
+//
+// For more information, please refer to <http://unlicense.org>
+
+use crate::{
+    configs::{
+        CongestionMultiplier, CongestionThreshold, FeelessExemptCalls, HybridFees, MaxFeeMultiplier,
+    },
+    AccountId, Runtime, RuntimeCall,
+};
+use frame_support::{
+    dispatch::{DispatchClass, DispatchInfo},
+    traits::ConstBool,
+    weights::Weight,
+};
+use pallet_feeless::IsExempt;
+use pallet_transaction_payment::{Multiplier, MultiplierUpdate};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{
+    traits::{Convert, One, Zero},
+    BuildStorage, Perquintill,
+};
+
+fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::<Runtime>::default()
+        .build_storage()
+        .unwrap()
+        .into();
+    ext.execute_with(|| frame_system::Pallet::<Runtime>::set_block_number(1));
+    ext
+}
+
+fn batch(calls: Vec<RuntimeCall>) -> RuntimeCall {
+    RuntimeCall::Utility(pallet_utility::Call::batch { calls })
+}
+
+#[test]
+fn sudo_calls_are_exempt_for_the_sudo_key_only() {
+    new_test_ext().execute_with(|| {
+        let key: AccountId = Sr25519Keyring::Alice.to_account_id();
+        let other: AccountId = Sr25519Keyring::Bob.to_account_id();
+        let sudo = RuntimeCall::Sudo(pallet_sudo::Call::set_key {
+            new: other.clone().into(),
+        });
+        assert!(!FeelessExemptCalls::is_exempt(&key, &sudo));
+
+        assert!(pallet_sudo::Pallet::<Runtime>::set_key(
+            frame_system::RawOrigin::Root.into(),
+            key.clone().into()
+        )
+        .is_ok());
+        assert!(FeelessExemptCalls::is_exempt(&key, &sudo));
+        assert!(FeelessExemptCalls::is_exempt(
+            &key,
+            &batch(vec![sudo.clone()])
+        ));
+        assert!(!FeelessExemptCalls::is_exempt(&other, &sudo));
+        assert!(!FeelessExemptCalls::is_exempt(&other, &batch(vec![sudo])));
+    });
+}
+
+#[test]
+fn council_votes_are_exempt_for_members_only() {
+    new_test_ext().execute_with(|| {
+        let member: AccountId = Sr25519Keyring::Alice.to_account_id();
+        let other: AccountId = Sr25519Keyring::Bob.to_account_id();
+        pallet_collective::Members::<Runtime, crate::configs::CouncilCollective>::put(vec![
+            member.clone()
+        ]);
+        let vote = RuntimeCall::Council(pallet_collective::Call::vote {
+            proposal: Default::default(),
+            index: 0,
+            approve: true,
+        });
+        let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+
+        assert!(FeelessExemptCalls::is_exempt(&member, &vote));
+        assert!(!FeelessExemptCalls::is_exempt(&other, &vote));
+        assert!(!FeelessExemptCalls::is_exempt(
+            &member,
+            &batch(vec![vote.clone(), remark.clone()])
+        ));
+        assert!(!FeelessExemptCalls::is_exempt(&member, &remark));
+
+        // The node tells the calls that may be exempt without the storage.
+        assert!(FeelessExemptCalls::matches(&vote, &|_| true));
+        assert!(!FeelessExemptCalls::matches(&remark, &|_| true));
+        assert!(!FeelessExemptCalls::matches(&batch(vec![]), &|_| true));
+    });
+}
+
+#[test]
+fn exemption_checks_are_weighed_once_per_storage_read() {
+    let sudo = RuntimeCall::Sudo(pallet_sudo::Call::remove_key {});
+    let vote = RuntimeCall::Council(pallet_collective::Call::vote {
+        proposal: Default::default(),
+        index: 0,
+        approve: true,
+    });
+    let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+    let read = <Runtime as frame_system::Config>::DbWeight::get().reads(1);
+
+    assert_eq!(FeelessExemptCalls::weight(&remark), Weight::zero());
+    assert_eq!(
+        FeelessExemptCalls::weight(&vote).ref_time(),
+        read.ref_time()
+    );
+    assert_eq!(
+        FeelessExemptCalls::weight(&batch(vec![vote.clone(); 10])),
+        FeelessExemptCalls::weight(&vote)
+    );
+    assert_eq!(
+        FeelessExemptCalls::weight(&batch(vec![sudo.clone(), vote.clone()])),
+        FeelessExemptCalls::weight(&sudo) + FeelessExemptCalls::weight(&vote)
+    );
+}
+
+/// Fills the normal class of the current block to `percent` of its maximum weight.
+fn fill_block(percent: u64) {
+    let max = crate::configs::RuntimeBlockWeights::get()
+        .get(DispatchClass::Normal)
+        .max_total
+        .unwrap();
+    frame_system::BlockWeight::<Runtime>::mutate(|weight| {
+        weight.set(
+            Weight::from_parts(max.ref_time() * percent / 100, 0),
+            DispatchClass::Normal,
+        )
+    });
+}
+
+#[test]
+fn fee_multiplier_follows_congestion() {
+    type Update = CongestionMultiplier<HybridFees>;
+    let threshold = CongestionThreshold::get() * 100u64;
+    new_test_ext().execute_with(|| {
+        fill_block(threshold);
+        assert_eq!(Update::convert(Multiplier::zero()), Multiplier::zero());
+        assert_eq!(Update::convert(Multiplier::from_u32(8)), Multiplier::zero());
+
+        fill_block(threshold + 1);
+        assert_eq!(Update::convert(Multiplier::zero()), Multiplier::one());
+        assert_eq!(
+            Update::convert(Multiplier::from_u32(8)),
+            Multiplier::from_u32(16)
+        );
+        assert_eq!(
+            Update::convert(MaxFeeMultiplier::get()),
+            MaxFeeMultiplier::get()
+        );
+    });
+}
+
+#[test]
+fn fees_are_only_charged_under_congestion() {
+    new_test_ext().execute_with(|| {
+        let info = DispatchInfo {
+            call_weight: Weight::from_parts(1_000_000, 0),
+            ..Default::default()
+        };
+        let fee = || pallet_transaction_payment::Pallet::<Runtime>::compute_fee(100, &info, 0);
+
+        pallet_transaction_payment::NextFeeMultiplier::<Runtime>::put(Multiplier::zero());
+        assert_eq!(fee(), 0);
+
+        pallet_transaction_payment::NextFeeMultiplier::<Runtime>::put(Multiplier::one());
+        assert!(fee() > 0);
+    });
+}
+
+#[test]
+fn fully_feeless_without_hybrid_fees() {
+    type Update = CongestionMultiplier<ConstBool<false>>;
+    new_test_ext().execute_with(|| {
+        fill_block(100);
+        assert_eq!(Update::convert(Multiplier::zero()), Multiplier::zero());
+        assert_eq!(Update::convert(Multiplier::from_u32(8)), Multiplier::zero());
+        assert_eq!(Update::target(), Perquintill::zero());
+    });
+}
+
+#[test]
+fn genesis_council_members_are_unlimited() {
+    for preset in crate::genesis_config_presets::preset_names() {
+        let patch: serde_json::Value =
+            serde_json::from_slice(&crate::genesis_config_presets::get_preset(&preset).unwrap())
+                .unwrap();
+        let unlimited: Vec<_> = patch["account"]["statuses"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|status| status[1] == "Unlimited")
+            .map(|status| &status[0])
+            .collect();
+        for member in patch["council"]["members"].as_array().unwrap() {
+            assert!(unlimited.contains(&member), "{member} is not unlimited");
+        }
+    }
+}